@@ -1 +1,8 @@
-cargo run ./resources/test_db/small.sql --output 1mb
+cargo build --release
+
+cp ./target/release/sql-split-cli ./resources/output
+
+cd ./resources/output
+time ./sql-split-cli big.sql --output 400kb
+time ./sql-split-cli big.sql --output 400kb --byte-scan
+cd -
@@ -0,0 +1,9 @@
+CREATE TABLE `files` (
+  `id` int NOT NULL,
+  `name` varchar(64) DEFAULT NULL,
+  `data` longblob,
+  `hash` varbinary(4) DEFAULT NULL,
+  PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+INSERT INTO `files` VALUES (1,'a.txt',0x68690A,_binary 'ab'),(2,NULL,NULL,''),(3,'NULL',X'00FF',NULL),(4,'',0x,0xCAFE);
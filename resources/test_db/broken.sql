@@ -0,0 +1,12 @@
+CREATE TABLE `users` (
+  `id` int NOT NULL,
+  `name` varchar(32) DEFAULT NULL
+);
+
+INSERT INTO `users` VALUES (1,'alice'),
+(2,'bob'),
+(3,'carol')),
+(4,'dave');
+INSERT INTO `users` VALUES (5,'eve');
+INSERT INTO `users` VALUES (6,'frank';
+INSERT INTO `users` VALUES (7,'grace');
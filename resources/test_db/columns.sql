@@ -0,0 +1,11 @@
+CREATE TABLE `users` (
+  `id` int NOT NULL,
+  `name` varchar(50) DEFAULT NULL,
+  `data` blob,
+  PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+INSERT INTO `users` (`id`, `name`, `data`) VALUES (1,'alice',0x61);
+-- the same columns in another order
+INSERT INTO `users` (`data`, `ID`, `name`) VALUES (0x62,2,'bob'),(NULL,3,'carol');
+INSERT INTO `users` VALUES (4,'dave',0x64);
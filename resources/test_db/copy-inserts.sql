@@ -0,0 +1,21 @@
+--
+-- PostgreSQL database dump
+--
+
+CREATE TABLE public.log (
+    line text
+);
+
+COPY public.log (line) FROM stdin;
+INSERT INTO users VALUES (1, 'alice'),
+(2, 'bob');
+INSERT INTO users VALUES (3, 'carol');
+\.
+
+INSERT INTO public.log VALUES ('after the copy'),
+('second row');
+
+COPY public.log (line) FROM stdin;
+(4, 'dave'),
+(5, 'eve');
+\.
@@ -0,0 +1,33 @@
+DROP TABLE IF EXISTS `audit`;
+CREATE TABLE `audit` (
+  `id` int NOT NULL AUTO_INCREMENT,
+  `note` varchar(64) DEFAULT NULL,
+  PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+INSERT INTO `audit` VALUES (1,'first'),
+(2,'second');
+DELIMITER ;;
+CREATE PROCEDURE `log_twice`(IN message VARCHAR(64))
+BEGIN
+INSERT INTO `audit` (`note`) VALUES (message);
+INSERT INTO `audit` (`note`) VALUES (CONCAT(message, ' again'));
+END ;;
+CREATE PROCEDURE `log_rows`()
+BEGIN
+INSERT INTO `audit` (`note`) VALUES ('a'),
+('b'),
+('c');
+INSERT INTO `audit` (`note`) VALUES ('d');
+END ;;
+DELIMITER ;
+INSERT INTO `audit` VALUES (3,'third'),
+(4,'fourth');
+DELIMITER $$
+CREATE TRIGGER `audit_note` BEFORE INSERT ON `audit` FOR EACH ROW
+BEGIN
+SET NEW.note = TRIM(NEW.note);
+INSERT INTO `audit_log` VALUES (NEW.id);
+END $$
+DELIMITER ;
+INSERT INTO `audit` VALUES (5,'fifth');
@@ -0,0 +1,47 @@
+USE [shop]
+GO
+/****** Object:  Table [dbo].[users]    Script Date: 10/19/2026 09:12:41 ******/
+SET ANSI_NULLS ON
+GO
+SET QUOTED_IDENTIFIER ON
+GO
+CREATE TABLE [dbo].[users](
+	[id] [int] IDENTITY(1,1) NOT NULL,
+	[name] [nvarchar](50) NOT NULL,
+	[e mail] [nvarchar](100) NULL,
+	[avatar] [varbinary](max) NULL,
+ CONSTRAINT [PK_users] PRIMARY KEY CLUSTERED 
+(
+	[id] ASC
+)WITH (PAD_INDEX = OFF, STATISTICS_NORECOMPUTE = OFF) ON [PRIMARY]
+) ON [PRIMARY] TEXTIMAGE_ON [PRIMARY]
+GO
+CREATE TABLE [dbo].[orders](
+	[id] [int] IDENTITY(1,1) NOT NULL,
+	[user_id] [int] NOT NULL,
+	[total] [decimal](10, 2) NULL,
+	[note] [nvarchar](max) NULL
+) ON [PRIMARY]
+GO
+SET IDENTITY_INSERT [dbo].[users] ON 
+
+INSERT [dbo].[users] ([id], [name], [e mail], [avatar]) VALUES (1, N'alice', N'alice@example.com', 0x89504E47)
+INSERT [dbo].[users] ([id], [name], [e mail], [avatar]) VALUES (2, N'o''brien', NULL, NULL)
+INSERT [dbo].[users] ([id], [name], [e mail], [avatar]) VALUES (3, N'carol', N'c;d@example.com
+GO on a line of its own', NULL)
+INSERT [dbo].[users] ([id], [name], [e mail], [avatar]) VALUES (4, N'C:\temp\', N'dave@example.com', NULL)
+SET IDENTITY_INSERT [dbo].[users] OFF
+GO
+SET IDENTITY_INSERT [dbo].[orders] ON 
+
+INSERT [dbo].[orders] ([id], [user_id], [total], [note]) VALUES (1, 1, CAST(19.99 AS Decimal(10, 2)), N'first order')
+INSERT [dbo].[orders] ([id], [user_id], [total], [note]) VALUES (2, 2, CAST(5.00 AS Decimal(10, 2)), NULL)
+INSERT [dbo].[orders] ([id], [user_id], [total], [note]) VALUES (3, 1, CAST(120.50 AS Decimal(10, 2)), N'gift; wrap it')
+INSERT [dbo].[orders] ([id], [user_id], [total], [note]) VALUES (4, 4, CAST(7.25 AS Decimal(10, 2)), N'say "hi"')
+SET IDENTITY_INSERT [dbo].[orders] OFF
+GO
+ALTER TABLE [dbo].[orders]  WITH CHECK ADD  CONSTRAINT [FK_orders_users] FOREIGN KEY([user_id])
+REFERENCES [dbo].[users] ([id])
+GO
+ALTER TABLE [dbo].[orders] CHECK CONSTRAINT [FK_orders_users]
+GO
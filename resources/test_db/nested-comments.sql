@@ -0,0 +1,18 @@
+--
+-- comments inside comments, as PostgreSQL and SQL Server allow them
+--
+
+/* outer /* inner */ still a comment; DROP TABLE hello; */
+
+CREATE TABLE hello (
+  id integer NOT NULL /* the /* key */ of a row */,
+  name text DEFAULT 'it''s /* not a comment */'
+);
+
+/*
+ * /*
+ *  * disabled: isn't loaded any more
+ *  */
+ * INSERT INTO hello VALUES (0, 'zero');
+ */
+INSERT INTO hello VALUES (1, 'one'), (2, '/* two */');
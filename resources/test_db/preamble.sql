@@ -0,0 +1,22 @@
+-- phpMyAdmin SQL Dump
+-- version 5.2.0
+
+SET SQL_MODE = "NO_AUTO_VALUE_ON_ZERO";
+SET time_zone = "+00:00";
+
+CREATE TABLE `users` (
+  `id` int(11) NOT NULL,
+  `name` varchar(50) NOT NULL,
+  `email` varchar(100) DEFAULT NULL,
+  PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+INSERT INTO `users` (`id`, `name`, `email`) VALUES
+(1, 'alice', 'alice@example.com'),
+(2, 'bob', 'bob@example.com'),
+(3, 'carol', NULL),
+(4, 'dave', 'dave@example.com'),
+(5, 'erin', 'erin@example.com'),
+(6, 'frank', 'frank@example.com');
+
+SET FOREIGN_KEY_CHECKS=1;
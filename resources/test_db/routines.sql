@@ -0,0 +1,40 @@
+-- MySQL dump 10.13  Distrib 8.0.32, for Linux (x86_64)
+--
+-- Host: localhost    Database: shop
+-- ------------------------------------------------------
+-- Server version	8.0.32
+
+/*!50503 SET NAMES utf8mb4 */;
+
+DROP TABLE IF EXISTS `orders`;
+CREATE TABLE `orders` (
+  `id` int NOT NULL AUTO_INCREMENT,
+  `total` decimal(10,2) DEFAULT NULL,
+  PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+LOCK TABLES `orders` WRITE;
+INSERT INTO `orders` VALUES (1,19.99),(2,5.00);
+UNLOCK TABLES;
+/*!50003 SET @saved_cs_client      = @@character_set_client */ ;
+DELIMITER ;;
+/*!50003 CREATE*/ /*!50017 DEFINER=`root`@`localhost`*/ /*!50003 TRIGGER `orders_total` BEFORE INSERT ON `orders` FOR EACH ROW BEGIN
+  IF NEW.total < 0 THEN
+    SET NEW.total = 0;
+  END IF;
+END */;;
+DELIMITER ;
+/*!50003 SET character_set_client  = @saved_cs_client */ ;
+
+--
+-- Dumping routines for database 'shop'
+--
+DELIMITER ;;
+CREATE DEFINER=`root`@`localhost` PROCEDURE `order_count`(OUT total INT)
+BEGIN
+  SELECT COUNT(*) INTO total FROM orders;
+  SELECT 'done;' AS status;
+END ;;
+DELIMITER ;
+
+INSERT INTO `orders` VALUES (3,120.50);
@@ -0,0 +1,6 @@
+-- mysqldump --skip-extended-insert, one row per INSERT
+
+INSERT INTO `users` VALUES (1,'alice');
+INSERT INTO `users` VALUES (2,'bob');
+INSERT INTO `users` VALUES (3,'carol'),(4,'dave');
+INSERT INTO `users` VALUES (5,'erin');
@@ -0,0 +1,20 @@
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS "users" (id INTEGER PRIMARY KEY, name TEXT NOT NULL, "e mail" TEXT, avatar BLOB);
+INSERT INTO users VALUES(1,'alice','alice@example.com',X'89504e47');
+INSERT INTO users VALUES(2,'o''brien',NULL,NULL);
+INSERT INTO users VALUES(3,'carol','c;d@example.com',X'00ff');
+INSERT INTO users VALUES(4,'C:\temp\','dave@example.com',NULL);
+CREATE TABLE orders (id INTEGER PRIMARY KEY AUTOINCREMENT, user_id INTEGER REFERENCES users(id), total REAL, note TEXT);
+INSERT INTO orders VALUES(1,1,19.98999999999999843,'first order');
+INSERT INTO orders VALUES(2,2,5.0,NULL);
+INSERT INTO orders VALUES(3,1,120.5,'gift; wrap it');
+INSERT INTO orders VALUES(4,4,7.25,'say "hi"');
+PRAGMA writable_schema=ON;
+CREATE TABLE IF NOT EXISTS sqlite_sequence(name,seq);
+DELETE FROM sqlite_sequence;
+INSERT INTO sqlite_sequence VALUES('orders',4);
+CREATE VIEW big_orders AS SELECT * FROM orders WHERE total > 100;
+CREATE INDEX orders_user ON orders(user_id);
+PRAGMA writable_schema=OFF;
+COMMIT;
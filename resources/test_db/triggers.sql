@@ -0,0 +1,32 @@
+CREATE TABLE orders (id INTEGER PRIMARY KEY, total REAL, begin_date TEXT, end_date TEXT, status TEXT);
+CREATE TABLE audit (order_id INTEGER, note TEXT);
+CREATE TRIGGER orders_audit AFTER UPDATE ON orders
+BEGIN
+  INSERT INTO audit VALUES (NEW.id, 'updated; total ' || NEW.total);
+  UPDATE orders SET status = CASE WHEN NEW.total > 100 THEN 'big' ELSE 'small' END WHERE id = NEW.id;
+END;
+CREATE TEMP TRIGGER orders_end AFTER UPDATE OF end_date ON orders
+WHEN NEW.end_date IS NOT NULL
+BEGIN
+  DELETE FROM audit WHERE order_id = OLD.id;
+END;
+INSERT INTO orders VALUES (1, 19.99, '2024-01-01', NULL, NULL);
+CREATE OR REPLACE PROCEDURE close_orders(v_end DATE)
+BEGIN
+  DECLARE n INT;
+  SET n = 0;
+  orders_loop: LOOP
+    IF n > 10 THEN
+      LEAVE orders_loop;
+    END IF;
+    SET n = n + 1;
+  END LOOP;
+  CASE n
+    WHEN 0 THEN UPDATE orders SET end_date = v_end;
+    ELSE BEGIN
+      UPDATE orders SET status = 'closed';
+    END;
+  END CASE;
+END;
+CREATE FUNCTION twice(n INT) RETURNS INT RETURN n * 2;
+SELECT CASE WHEN 1 THEN 'begin' END;
@@ -0,0 +1,50 @@
+// the cli is a binary, the parser is pulled in from its sources. the
+// parts it does not use and the imports of its tests go unused here.
+#![allow(dead_code, unused_imports)]
+
+#[path = "../dialect.rs"]
+mod dialect;
+#[path = "../parser/mod.rs"]
+mod parser;
+#[path = "../tokenizer/mod.rs"]
+mod tokenizer;
+
+use criterion::{
+    criterion_group,
+    criterion_main,
+    Criterion,
+    Throughput,
+};
+use std::fs::File;
+use dialect::Dialect;
+use parser::parser::Parser;
+
+const DUMPS: [&str; 2] = [
+    "../resources/test_db/big.sql",
+    "../resources/test_db/mysqldump.sql",
+];
+
+fn parse(path: &str, fast_scan: bool) -> usize {
+    let mut parser = Parser::from_file(File::open(path).unwrap(), fast_scan, Dialect::Mysql);
+    let mut items = 0;
+    while parser.token_stream().unwrap().is_some() {
+        items += 1;
+    }
+    items
+}
+
+// the whole dump through Parser and Tokenizer, with the memchr fast path
+// and with --byte-scan
+pub fn scan_benchmark(c: &mut Criterion) {
+    for path in DUMPS {
+        let size = std::fs::metadata(path).unwrap().len();
+        let mut group = c.benchmark_group(format!("parse {}", path.rsplit('/').next().unwrap()));
+        group.throughput(Throughput::Bytes(size));
+        group.bench_function("byte-scan", |b| b.iter(|| parse(path, false)));
+        group.bench_function("memchr", |b| b.iter(|| parse(path, true)));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, scan_benchmark);
+criterion_main!(benches);
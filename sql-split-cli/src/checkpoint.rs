@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::translate::Translator;
+
+pub const CHECKPOINT_FILE: &str = "./sql-split.checkpoint";
+
+// state of the splitter right after a chunk was completed. enough to
+// pick the split up again from that point with --resume.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    // size of the input file, used to refuse resuming a different dump
+    pub input_size: usize,
+    // input offset right after the last completed chunk
+    pub offset: usize,
+    // number of completed chunks
+    pub chunks: usize,
+    pub last_insert: Vec<u8>,
+    pub preamble: Vec<u8>,
+    // the chunk was cut in the middle of an INSERT
+    #[serde(default)]
+    pub open_insert: bool,
+    // BEGIN statement of a transaction still open in the input
+    #[serde(default)]
+    pub transaction: Option<Vec<u8>>,
+    // columns of the --where tables from the CREATE TABLE statements
+    // already split
+    #[serde(default)]
+    pub filter_columns: HashMap<String, Vec<String>>,
+    // what --translate reported and the tables it set sequences for
+    #[serde(default)]
+    pub translator: Option<Translator>,
+}
+
+impl Checkpoint {
+    // written to a temp file first so a crash never leaves half a checkpoint
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_vec(self).map_err(|e| e.to_string())?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json).map_err(|e| format!("unable to write checkpoint: {}", e))?;
+        fs::rename(&tmp, path).map_err(|e| format!("unable to write checkpoint: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read(path).map_err(|_| String::from("no checkpoint found to resume from"))?;
+        serde_json::from_slice(&json).map_err(|e| format!("invalid checkpoint: {}", e))
+    }
+}
+
+
+#[cfg(test)]
+mod checkpoint_test{
+    use std::collections::HashMap;
+    use std::env;
+    use crate::translate::Translator;
+    use super::Checkpoint;
+
+    #[test]
+    fn save_and_load(){
+        let path = env::temp_dir().join("sql-split-checkpoint-test");
+        let checkpoint = Checkpoint {
+            input_size: 1024,
+            offset: 512,
+            chunks: 3,
+            last_insert: b"INSERT INTO `hello` VALUES ".to_vec(),
+            preamble: b"SET NAMES latin1;\n".to_vec(),
+            open_insert: true,
+            transaction: Some(b"BEGIN TRANSACTION;".to_vec()),
+            filter_columns: HashMap::from([(String::from("hello"), vec![String::from("id")])]),
+            translator: Some(Translator::default()),
+        };
+
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), checkpoint);
+        std::fs::remove_file(&path).unwrap();
+        assert!(Checkpoint::load(&path).is_err());
+    }
+}
@@ -1,8 +1,75 @@
-use clap::App;
+use clap::{App, ArgMatches};
 use std::fs::File;
 use std::result::Result;
 use std::str;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use crate::manifest::Manifest;
+use crate::splitter::OnError;
+use crate::convert::Format;
+use crate::load_script::Target;
+use crate::dialect::Dialect;
+use crate::translate::Translation;
+use crate::filter::Condition;
+use crate::parallel::SEGMENT_SIZE;
+
+pub enum Command {
+    Split(Args),
+    Stats(StatsArgs),
+    Extract(ExtractArgs),
+    Validate(ValidateArgs),
+    Merge(MergeArgs),
+    Convert(ConvertArgs),
+}
+
+pub struct ConvertArgs {
+    pub file: File,
+    pub dialect: Dialect,
+    pub format: Format,
+    pub write: Option<usize>,
+    pub load_script: Option<Target>,
+}
+
+pub struct MergeArgs {
+    pub chunks: Vec<PathBuf>,
+    pub coalesce: bool,
+    pub dialect: Dialect,
+}
+
+pub struct ValidateArgs {
+    pub file: File,
+    pub dialect: Dialect,
+}
+
+pub struct ExtractArgs {
+    pub file: File,
+    pub dialect: Dialect,
+    pub table: String,
+    pub stop_early: bool,
+}
+
+pub struct StatsArgs {
+    pub file: File,
+    pub dialect: Dialect,
+    pub json: bool,
+}
+
+pub struct Args {
+    pub file: File,
+    pub path: PathBuf,
+    pub write: usize,
+    pub fast_scan: bool,
+    pub threads: usize,
+    pub segment_size: usize,
+    pub resume: bool,
+    pub replay_preamble: bool,
+    pub on_error: OnError,
+    pub verify: bool,
+    pub rows_per_insert: Option<usize>,
+    pub dialect: Dialect,
+    pub translate: Option<Translation>,
+    pub chunk_transactions: bool,
+    pub filter: Vec<Condition>,
+}
 
 fn parse_size(input: Option<&str>, arg_name: &str) -> Result<usize, String> {
     match input {
@@ -43,24 +110,196 @@ fn parse_size(input: Option<&str>, arg_name: &str) -> Result<usize, String> {
 }
 
 
-pub fn args() -> (Result<File, &'static str>, Result<usize, String>) {
-    let yaml = load_yaml!("../cli.yml");
-    let matches = App::from_yaml(yaml).get_matches();
-    let write_buffer = matches.value_of("OUTPUT_SIZE");
-    let file = match matches.value_of("INPUT") {
+fn parse_threads(input: Option<&str>) -> Result<usize, String> {
+    match input {
+        Some(value) => match value.parse::<usize>() {
+            Ok(threads) if threads > 0 => Ok(threads),
+            _ => Err(String::from("threads should be a number greater than 0")),
+        },
+        None => Ok(1),
+    }
+}
+
+fn parse_rows(input: Option<&str>) -> Result<Option<usize>, String> {
+    match input {
+        Some(value) => match value.parse::<usize>() {
+            Ok(rows) if rows > 0 => Ok(Some(rows)),
+            _ => Err(String::from("rows-per-insert should be a number greater than 0")),
+        },
+        None => Ok(None),
+    }
+}
+
+fn parse_on_error(input: Option<&str>) -> OnError {
+    match input {
+        Some("skip") => OnError::Skip,
+        Some("quarantine") => OnError::Quarantine,
+        _ => OnError::Abort,
+    }
+}
+
+fn open_input(input: Option<&str>) -> Result<(File, PathBuf), String> {
+    match input {
         Some(file) => {
             let path = Path::new(file);
             if path.exists(){
                 match File::open(path) {
-                    Ok(file) => Ok(file),
-                    Err(_) => Err("Unable to open file"),
+                    Ok(file) => Ok((file, path.to_path_buf())),
+                    Err(_) => Err(String::from("Unable to open file")),
                 }
             }else{
-                Err("File path is invalid")
+                Err(String::from("File path is invalid"))
             }
         },
-        None => Err("File name is missing"),
+        None => Err(String::from("File name is missing")),
+    }
+}
+
+fn stats_args(matches: &ArgMatches) -> Result<StatsArgs, String> {
+    let (file, path) = open_input(matches.value_of("INPUT"))?;
+    Ok(StatsArgs {
+        file,
+        dialect: parse_dialect(matches.value_of("DIALECT"), &path)?,
+        json: matches.value_of("FORMAT") == Some("json"),
+    })
+}
+
+fn extract_args(matches: &ArgMatches) -> Result<ExtractArgs, String> {
+    let (file, path) = open_input(matches.value_of("INPUT"))?;
+    Ok(ExtractArgs {
+        file,
+        dialect: parse_dialect(matches.value_of("DIALECT"), &path)?,
+        table: matches.value_of("TABLE").unwrap_or_default().to_string(),
+        stop_early: matches.is_present("STOP_EARLY"),
+    })
+}
+
+fn validate_args(matches: &ArgMatches) -> Result<ValidateArgs, String> {
+    let (file, path) = open_input(matches.value_of("INPUT"))?;
+    Ok(ValidateArgs {
+        file,
+        dialect: parse_dialect(matches.value_of("DIALECT"), &path)?,
+    })
+}
+
+// chunk files are given one by one or listed by a manifest.json
+fn merge_args(matches: &ArgMatches) -> Result<MergeArgs, String> {
+    let chunks: Vec<PathBuf> = match matches.value_of("MANIFEST") {
+        Some(manifest) => {
+            let manifest_path = Path::new(manifest);
+            let dir = manifest_path.parent().unwrap_or(Path::new("."));
+            Manifest::load(manifest_path)?.chunks.into_iter().map(|chunk| dir.join(chunk.file)).collect()
+        },
+        None => matches.values_of("CHUNKS").map(|chunks| chunks.map(PathBuf::from).collect()).unwrap_or_default(),
+    };
+
+    if chunks.is_empty() {
+        return Err(String::from("no chunks to merge"))
+    }
+
+    for chunk in &chunks {
+        if !chunk.exists() {
+            return Err(format!("chunk {} does not exist", chunk.display()))
+        }
+    }
+
+    Ok(MergeArgs {
+        dialect: parse_dialect(matches.value_of("DIALECT"), &chunks[0])?,
+        chunks,
+        coalesce: matches.is_present("COALESCE"),
+    })
+}
+
+fn convert_args(matches: &ArgMatches) -> Result<ConvertArgs, String> {
+    let (file, path) = open_input(matches.value_of("INPUT"))?;
+    let write = match matches.value_of("OUTPUT_SIZE") {
+        Some(size) => Some(parse_size(Some(size), "output-size")?),
+        None => None,
     };
+    let format = match matches.value_of("TO") {
+        Some("tsv") => Format::Tsv,
+        Some("jsonl") => Format::Jsonl,
+        _ => Format::Csv,
+    };
+    let load_script = match matches.value_of("LOAD_SCRIPT") {
+        Some(_) if format == Format::Jsonl => return Err(String::from("load-script needs --to csv or tsv")),
+        Some("postgres") => Some(Target::Postgres),
+        Some(_) => Some(Target::Mysql),
+        None => None,
+    };
+    Ok(ConvertArgs {
+        file,
+        dialect: parse_dialect(matches.value_of("DIALECT"), &path)?,
+        format,
+        write,
+        load_script,
+    })
+}
 
-    (file, parse_size(write_buffer, "output-size"))
-}
\ No newline at end of file
+// `auto` looks at the top of `sample`
+fn parse_dialect(input: Option<&str>, sample: &Path) -> Result<Dialect, String> {
+    match input {
+        Some("auto") => Dialect::detect(sample).map_err(|e| format!("unable to detect the dialect: {}", e)),
+        Some("postgres") => Ok(Dialect::Postgres),
+        Some("sqlite") => Ok(Dialect::Sqlite),
+        Some("mssql") => Ok(Dialect::Mssql),
+        Some("ansi") => Ok(Dialect::Ansi),
+        _ => Ok(Dialect::Mysql),
+    }
+}
+
+fn parse_translation(input: Option<&str>) -> Option<Translation> {
+    match input {
+        Some("postgres") => Some(Translation::Postgres),
+        _ => None,
+    }
+}
+
+fn split_args(matches: &ArgMatches) -> Result<Args, String> {
+    let (file, path) = open_input(matches.value_of("INPUT"))?;
+    let translate = parse_translation(matches.value_of("TRANSLATE"));
+    if translate.is_some() && matches.is_present("VERIFY") {
+        return Err(String::from("verify can not compare translated chunks with the input"))
+    }
+    let on_error = parse_on_error(matches.value_of("ON_ERROR"));
+    if on_error != OnError::Abort && matches.is_present("VERIFY") {
+        return Err(String::from("verify can not compare chunks with skipped statements with the input"))
+    }
+    let filter = matches.values_of("WHERE").map_or(Ok(vec![]), |exprs| exprs.map(Condition::parse).collect())?;
+    if !filter.is_empty() && matches.is_present("VERIFY") {
+        return Err(String::from("verify can not compare filtered chunks with the input"))
+    }
+    Ok(Args {
+        file,
+        write: parse_size(matches.value_of("OUTPUT_SIZE"), "output-size")?,
+        fast_scan: !matches.is_present("BYTE_SCAN"),
+        threads: parse_threads(matches.value_of("THREADS"))?,
+        segment_size: match matches.value_of("SEGMENT_SIZE") {
+            Some(_) => parse_size(matches.value_of("SEGMENT_SIZE"), "segment-size")?,
+            None => SEGMENT_SIZE,
+        },
+        resume: matches.is_present("RESUME"),
+        replay_preamble: matches.is_present("REPLAY_PREAMBLE"),
+        on_error,
+        verify: matches.is_present("VERIFY"),
+        rows_per_insert: parse_rows(matches.value_of("ROWS_PER_INSERT"))?,
+        dialect: parse_dialect(matches.value_of("DIALECT"), &path)?,
+        path,
+        translate,
+        chunk_transactions: matches.is_present("CHUNK_TRANSACTIONS"),
+        filter,
+    })
+}
+
+pub fn command() -> Result<Command, String> {
+    let yaml = load_yaml!("../cli.yml");
+    let matches = App::from_yaml(yaml).get_matches();
+    match matches.subcommand() {
+        ("stats", Some(matches)) => Ok(Command::Stats(stats_args(matches)?)),
+        ("extract", Some(matches)) => Ok(Command::Extract(extract_args(matches)?)),
+        ("validate", Some(matches)) => Ok(Command::Validate(validate_args(matches)?)),
+        ("merge", Some(matches)) => Ok(Command::Merge(merge_args(matches)?)),
+        ("convert", Some(matches)) => Ok(Command::Convert(convert_args(matches)?)),
+        _ => Ok(Command::Split(split_args(&matches)?)),
+    }
+}
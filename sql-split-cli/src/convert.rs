@@ -0,0 +1,639 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::dialect::Dialect;
+use crate::parser::parser::{StreamSource, TokenStream};
+use crate::parser::statement::{conditional_body, insert_columns, row, table_name, words, StatementKind};
+use crate::parser::table::{Column, CreateTable};
+use crate::parser::values::{parse_tuple, Value};
+use crate::tokenizer::token_err::TokenErr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Csv,
+    Tsv,
+    Jsonl,
+}
+
+pub enum ConvertErr {
+    Syntax(TokenErr),
+    Io(io::Error),
+}
+
+impl From<io::Error> for ConvertErr {
+    fn from(e: io::Error) -> Self {
+        ConvertErr::Io(e)
+    }
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Tsv => "tsv",
+            Format::Jsonl => "jsonl",
+        }
+    }
+
+    // csv quotes a field when it has to. NULL is the word NULL, which
+    // LOAD DATA reads as NULL when it is not quoted and COPY with
+    // `NULL 'NULL'`, so the string 'NULL' is quoted. tsv writes NULL as \N
+    // the way LOAD DATA and COPY read it.
+    fn field(&self, value: &[u8], null: bool, output: &mut Vec<u8>) {
+        match self {
+            Format::Csv if null => output.extend(b"NULL"),
+            Format::Csv => {
+                let quote = value.is_empty() || value.eq_ignore_ascii_case(b"NULL") ||
+                    value.iter().any(|byte| matches!(byte, b',' | b'"' | b'\n' | b'\r'));
+                if !quote {
+                    output.extend(value);
+                    return
+                }
+                output.push(b'"');
+                for byte in value {
+                    if *byte == b'"' {
+                        output.push(b'"');
+                    }
+                    output.push(*byte);
+                }
+                output.push(b'"');
+            },
+            Format::Tsv if null => output.extend(b"\\N"),
+            Format::Tsv => {
+                for byte in value {
+                    match byte {
+                        b'\\' => output.extend(b"\\\\"),
+                        b'\t' => output.extend(b"\\t"),
+                        b'\n' => output.extend(b"\\n"),
+                        b'\r' => output.extend(b"\\r"),
+                        _ => output.push(*byte),
+                    }
+                }
+            },
+            Format::Jsonl => unreachable!(),
+        }
+    }
+
+    fn delimited<'a>(&self, fields: impl Iterator<Item = (&'a [u8], bool)>) -> Vec<u8> {
+        let separator = if *self == Format::Tsv { b'\t' } else { b',' };
+        let mut line = vec![];
+        for (index, (value, null)) in fields.enumerate() {
+            if index > 0 {
+                line.push(separator);
+            }
+            self.field(value, null, &mut line);
+        }
+        line.push(b'\n');
+        line
+    }
+
+    // first line of every file, json lines carry the names in each row
+    fn header(&self, columns: &[String]) -> Option<Vec<u8>> {
+        if columns.is_empty() || *self == Format::Jsonl {
+            return None
+        }
+        Some(self.delimited(columns.iter().map(|column| (column.as_bytes(), false))))
+    }
+
+    // values of binary columns are written as \x1F, which COPY reads into
+    // bytea and the mysql load script unhexes
+    fn line(&self, columns: &[String], binary: &[bool], values: &[Value]) -> Vec<u8> {
+        if *self != Format::Jsonl {
+            let fields: Vec<(Cow<[u8]>, bool)> = values.iter().enumerate().map(|(index, value)| match value {
+                Value::Null => (Cow::Borrowed(&[][..]), true),
+                Value::Blob(bytes) |
+                Value::String(bytes) if binary.get(index) == Some(&true) => (Cow::Owned(hex(b"\\x", bytes)), false),
+                Value::Int(int) => (Cow::Owned(int.to_string().into_bytes()), false),
+                Value::Blob(bytes) => (Cow::Owned(hex(b"0x", bytes)), false),
+                Value::String(value) => (Cow::Borrowed(&value[..]), false),
+                Value::Decimal(value) |
+                Value::Expr(value) => (Cow::Borrowed(*value), false),
+            }).collect();
+            return self.delimited(fields.iter().map(|(value, null)| (&value[..], *null)))
+        }
+
+        // columns the dump does not name are `column_N`
+        let mut line = vec![b'{'];
+        for (index, value) in values.iter().enumerate() {
+            if index > 0 {
+                line.push(b',');
+            }
+            let key = match columns.get(index) {
+                Some(column) => column.clone(),
+                None => format!("column_{}", index + 1),
+            };
+            line.extend(json_string(key.as_bytes()));
+            line.push(b':');
+            match value {
+                Value::Null => line.extend(b"null"),
+                Value::Int(int) => line.extend(int.to_string().as_bytes()),
+                Value::Decimal(value) if is_number(value) => line.extend(*value),
+                Value::Blob(bytes) => line.extend(json_string(&hex(b"0x", bytes))),
+                Value::String(value) => line.extend(json_string(value)),
+                Value::Decimal(value) |
+                Value::Expr(value) => line.extend(json_string(value)),
+            }
+        }
+        line.extend(b"}\n");
+        line
+    }
+}
+
+// blobs are written the way MySQL reads them back, 0x1F, and values of
+// binary columns the way Postgres does, \x1F
+fn hex(prefix: &[u8], bytes: &[u8]) -> Vec<u8> {
+    let mut output = prefix.to_vec();
+    for byte in bytes {
+        output.extend(format!("{:02X}", byte).as_bytes());
+    }
+    output
+}
+
+fn json_string(value: &[u8]) -> Vec<u8> {
+    serde_json::to_vec(&String::from_utf8_lossy(value)).unwrap_or_default()
+}
+
+// numbers JSON can hold as they are written, `0x1F` or `.5` are not
+fn is_number(value: &[u8]) -> bool {
+    let value = value.strip_prefix(b"-").unwrap_or(value);
+    let digits = |value: &[u8]| value.iter().take_while(|byte| byte.is_ascii_digit()).count();
+
+    let integer = digits(value);
+    if integer == 0 || (integer > 1 && value[0] == b'0') {
+        return false
+    }
+    let mut rest = &value[integer..];
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let len = digits(fraction);
+        if len == 0 {
+            return false
+        }
+        rest = &fraction[len..];
+    }
+    if let Some(exponent) = rest.strip_prefix(b"e").or_else(|| rest.strip_prefix(b"E")) {
+        let exponent = exponent.strip_prefix(b"+").or_else(|| exponent.strip_prefix(b"-")).unwrap_or(exponent);
+        let len = digits(exponent);
+        if len == 0 {
+            return false
+        }
+        rest = &exponent[len..];
+    }
+    rest.is_empty()
+}
+
+// where each column of an INSERT goes in the header of the table's file,
+// None when the columns are in the same order or not known
+fn positions(header: &[String], columns: &[String]) -> Result<Option<Vec<usize>>, &'static str> {
+    let same = |a: &String, b: &String| a.eq_ignore_ascii_case(b);
+    if header.is_empty() || columns.is_empty() || (header.len() == columns.len() && header.iter().zip(columns).all(|(a, b)| same(a, b))) {
+        return Ok(None)
+    }
+
+    let changed = "Columns of the INSERT are not the ones of the first INSERT into its table.";
+    if header.len() != columns.len() {
+        return Err(changed)
+    }
+    let positions: Vec<usize> = columns.iter()
+        .map(|column| header.iter().position(|name| same(name, column)).ok_or(changed))
+        .collect::<Result<_, _>>()?;
+    // a column named twice leaves another one out
+    let mut sorted = positions.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    if sorted.len() != positions.len() {
+        return Err(changed)
+    }
+    Ok(Some(positions))
+}
+
+// BINARY, VARBINARY, the BLOB types and bytea
+fn is_binary(column: &Column) -> bool {
+    let data_type = column.data_type.to_lowercase();
+    let base = data_type.split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or_default();
+    matches!(base, "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" | "bytea")
+}
+
+// a character set name that can go into a statement as it is
+fn charset(name: &str) -> Option<String> {
+    Some(name.to_string()).filter(|name| !name.is_empty() && name.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'_'))
+}
+
+// utf8mb4 of SET NAMES utf8mb4 [COLLATE ...]
+fn set_names(tokens: &[u8]) -> Option<String> {
+    let words = words(conditional_body(tokens), 3);
+    match words.as_slice() {
+        [set, names, name] if set.eq_ignore_ascii_case("set") && names.eq_ignore_ascii_case("names") => {
+            charset(name).filter(|name| !name.eq_ignore_ascii_case("default"))
+        },
+        _ => None,
+    }
+}
+
+// DEFAULT CHARSET=latin1 or CHARACTER SET = latin1 of a CREATE TABLE
+fn table_charset(create: &CreateTable) -> Option<String> {
+    create.options.iter()
+        .find(|(key, _)| {
+            let key = key.to_lowercase();
+            key.ends_with("charset") || key.ends_with("character set")
+        })
+        .and_then(|(_, value)| charset(value))
+}
+
+// a table that was converted, with its files in order
+pub struct ConvertedTable {
+    pub name: String,
+    pub columns: Vec<String>,
+    // whether each of the columns is binary
+    pub binary: Vec<bool>,
+    // encoding of the strings in the files, when the dump tells
+    pub charset: Option<String>,
+    pub files: Vec<PathBuf>,
+}
+
+// where the rows of a table went so far
+struct TableFile {
+    part: usize,
+    size: usize,
+    // rows in the current part
+    rows: usize,
+    header: Vec<String>,
+    // binary columns of the header
+    binary: Vec<bool>,
+    charset: Option<String>,
+}
+
+// writes the rows of every table to `<table>.csv`, `.tsv` or `.jsonl`,
+// or `<table>.1.csv`, `<table>.2.csv` ... when a size limit is given
+pub struct Converter {
+    format: Format,
+    // strings of the input have backslash escapes
+    backslash_escapes: bool,
+    max_size: Option<usize>,
+    dir: PathBuf,
+    // columns of the CREATE TABLE statements seen so far
+    columns: HashMap<String, Vec<Column>>,
+    // DEFAULT CHARSET of the CREATE TABLE statements seen so far
+    charsets: HashMap<String, String>,
+    // the last SET NAMES, the strings of the dump are in this encoding
+    names: Option<String>,
+    files: HashMap<String, TableFile>,
+    // tables in the order they showed up
+    tables: Vec<String>,
+    table: String,
+    // where the values of the current INSERT go in the header
+    positions: Option<Vec<usize>>,
+    output: Option<BufWriter<File>>,
+}
+
+impl Converter {
+    pub fn new(format: Format, dialect: Dialect, max_size: Option<usize>, dir: PathBuf) -> Self {
+        Self {
+            format,
+            backslash_escapes: dialect.syntax().backslash_escapes,
+            max_size,
+            dir,
+            columns: HashMap::new(),
+            charsets: HashMap::new(),
+            names: None,
+            files: HashMap::new(),
+            tables: vec![],
+            table: String::new(),
+            positions: None,
+            output: None,
+        }
+    }
+
+    fn path(&self, table: &str, part: usize) -> PathBuf {
+        let name = table.replace(['/', '\\'], "_");
+        match self.max_size {
+            Some(_) => self.dir.join(format!("{}.{}.{}", name, part, self.format.extension())),
+            None => self.dir.join(format!("{}.{}", name, self.format.extension())),
+        }
+    }
+
+    // opens the file of the current table. it is appended to when the
+    // table showed up before.
+    fn open(&mut self, new_part: bool) -> io::Result<()> {
+        self.close()?;
+        let file = self.files.get_mut(&self.table).unwrap();
+        let fresh = new_part || file.size == 0;
+        if new_part {
+            file.part += 1;
+            file.size = 0;
+            file.rows = 0;
+        }
+
+        let path = self.path(&self.table, self.files[&self.table].part);
+        let mut output = BufWriter::new(OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(!fresh)
+            .truncate(fresh)
+            .open(path)?);
+
+        let file = self.files.get_mut(&self.table).unwrap();
+        if let Some(header) = self.format.header(&file.header).filter(|_| fresh) {
+            output.write_all(&header)?;
+            file.size += header.len();
+        }
+        self.output = Some(output);
+        Ok(())
+    }
+
+    // the header and binary columns of a table come from its first
+    // INSERT, later ones may name the same columns in another order
+    fn insert(&mut self, insert_stmt: &[u8], pos: usize) -> Result<(), ConvertErr> {
+        let table = table_name(insert_stmt).unwrap_or_default();
+        let columns = self.columns.get(&table).cloned().unwrap_or_default();
+        let names = insert_columns(insert_stmt)
+            .unwrap_or_else(|| columns.iter().map(|column| column.name.clone()).collect());
+
+        if table != self.table || self.output.is_none() {
+            self.table = table;
+            if !self.files.contains_key(&self.table) {
+                let binary = names.iter().map(|name| {
+                    columns.iter().any(|column| column.name.eq_ignore_ascii_case(name) && is_binary(column))
+                }).collect();
+                let charset = self.names.clone().or_else(|| self.charsets.get(&self.table).cloned());
+                self.files.insert(self.table.clone(), TableFile { part: 1, size: 0, rows: 0, header: names.clone(), binary, charset });
+                self.tables.push(self.table.clone());
+            }
+            self.open(false)?;
+        }
+
+        self.positions = positions(&self.files[&self.table].header, &names)
+            .map_err(|text| ConvertErr::Syntax(TokenErr { text, pos }))?;
+        Ok(())
+    }
+
+    fn row(&mut self, tuple: &[u8], pos: usize) -> Result<(), ConvertErr> {
+        let mut values = parse_tuple(row(tuple), self.backslash_escapes).map_err(|text| ConvertErr::Syntax(TokenErr { text, pos }))?;
+        if let Some(positions) = &self.positions {
+            if values.len() != positions.len() {
+                return Err(ConvertErr::Syntax(TokenErr { text: "Values tuple does not have a value for every column of its INSERT.", pos }))
+            }
+            let mut ordered: Vec<Option<Value>> = (0..positions.len()).map(|_| None).collect();
+            for (value, position) in values.into_iter().zip(positions) {
+                ordered[*position] = Some(value);
+            }
+            values = ordered.into_iter().flatten().collect();
+        }
+        let file = &self.files[&self.table];
+        let line = self.format.line(&file.header, &file.binary, &values);
+
+        // a row bigger than the limit still gets a file of its own
+        let file = &self.files[&self.table];
+        if file.rows > 0 && self.max_size.is_some_and(|max| file.size + line.len() > max) {
+            self.open(true)?;
+        }
+
+        self.output.as_mut().unwrap().write_all(&line)?;
+        let file = self.files.get_mut(&self.table).unwrap();
+        file.size += line.len();
+        file.rows += 1;
+        Ok(())
+    }
+
+    pub fn run(&mut self, source: &mut dyn StreamSource) -> Result<(), ConvertErr> {
+        while let Some(item) = source.token_stream().map_err(ConvertErr::Syntax)? {
+            match item {
+                TokenStream::Insert(tokens, insert_stmt) => {
+                    let pos = source.position() - tokens.len();
+                    self.insert(&insert_stmt, pos)?;
+                    let start = insert_stmt.len().saturating_sub(1);
+                    self.row(&tokens[start..], pos + start)?;
+                },
+                TokenStream::ValuesTuple(tokens) => {
+                    let pos = source.position() - tokens.len();
+                    self.row(&tokens, pos)?;
+                },
+                TokenStream::Block(tokens, StatementKind::CreateTable(table)) => {
+                    let create = CreateTable::parse(&tokens);
+                    if let Some(charset) = create.as_ref().and_then(table_charset) {
+                        self.charsets.insert(table.clone(), charset);
+                    }
+                    self.columns.insert(table, create.map(|create| create.columns).unwrap_or_default());
+                },
+                TokenStream::Block(tokens, StatementKind::Set) => {
+                    if let Some(names) = set_names(&tokens) {
+                        self.names = Some(names);
+                    }
+                },
+                TokenStream::Block(..) => {},
+                TokenStream::Comment(_) |
+                TokenStream::SpaceOrLineFeed(_) => {},
+            }
+        }
+        Ok(())
+    }
+
+    pub fn close(&mut self) -> io::Result<()> {
+        match self.output.take() {
+            Some(mut output) => output.flush(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn tables(&self) -> Vec<ConvertedTable> {
+        self.tables.iter().map(|table| {
+            let file = &self.files[table];
+            ConvertedTable {
+                name: table.clone(),
+                columns: file.header.clone(),
+                binary: file.binary.clone(),
+                charset: file.charset.clone(),
+                files: (1..=file.part).map(|part| self.path(table, part)).collect(),
+            }
+        }).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod convert_test{
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::dialect::Dialect;
+    use crate::parser::parser::Parser;
+
+    use super::{is_number, ConvertErr, Converter, Format};
+
+    fn convert(path: &str, format: Format, max_size: Option<usize>, name: &str) -> PathBuf {
+        convert_as(path, Dialect::Mysql, format, max_size, name)
+    }
+
+    fn convert_as(path: &str, dialect: Dialect, format: Format, max_size: Option<usize>, name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("sql-split-convert-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut converter = Converter::new(format, dialect, max_size, dir.clone());
+        let mut parser = Parser::from_file(fs::File::open(path).unwrap(), true, dialect);
+        if converter.run(&mut parser).is_err() {
+            panic!("convert failed");
+        }
+        converter.close().unwrap();
+        dir
+    }
+
+    #[test]
+    fn csv(){
+        let dir = convert("../resources/test_db/mysqldump.sql", Format::Csv, None, "csv");
+        // no column list in the INSERTs, the header comes from CREATE TABLE
+        assert_eq!(fs::read_to_string(dir.join("users.csv")).unwrap(), "\
+            id,name,email,created_at\n\
+            1,alice,alice@example.com,2023-01-01 10:00:00\n\
+            2,bob,NULL,2023-01-02 11:30:00\n\
+            3,o'brien,ob@example.com,2023-01-03 09:15:00\n");
+        assert_eq!(fs::read_to_string(dir.join("orders.csv")).unwrap(), "\
+            id,user_id,tenant_id,total,note\n\
+            1,1,42,19.99,first order\n\
+            2,2,7,5.00,NULL\n\
+            3,1,42,120.50,gift; wrap it\n\
+            4,3,42,0.99,\"\"\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn binary_columns(){
+        // values of binary columns are hex whether written as blobs or
+        // strings, the string 'NULL' is quoted
+        let dir = convert("../resources/test_db/blobs.sql", Format::Csv, None, "binary");
+        assert_eq!(fs::read_to_string(dir.join("files.csv")).unwrap(), "\
+            id,name,data,hash\n\
+            1,a.txt,\\x68690A,\\x6162\n\
+            2,NULL,NULL,\\x\n\
+            3,\"NULL\",\\x00FF,NULL\n\
+            4,\"\",\\x,\\xCAFE\n");
+        fs::remove_dir_all(dir).unwrap();
+
+        let dir = convert("../resources/test_db/blobs.sql", Format::Tsv, None, "binary-tsv");
+        let files = fs::read_to_string(dir.join("files.tsv")).unwrap();
+        assert!(files.contains("2\t\\N\t\\N\t\\\\x\n"));
+        assert!(files.contains("3\tNULL\t\\\\x00FF\t\\N\n"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn column_order(){
+        // later INSERTs go under the header of the first one
+        let path = "../resources/test_db/columns.sql";
+        let dir = convert(path, Format::Csv, None, "columns");
+        assert_eq!(fs::read_to_string(dir.join("users.csv")).unwrap(), "\
+            id,name,data\n\
+            1,alice,\\x61\n\
+            2,bob,\\x62\n\
+            3,carol,NULL\n\
+            4,dave,\\x64\n");
+        fs::remove_dir_all(dir).unwrap();
+        let dir = convert(path, Format::Jsonl, None, "columns-jsonl");
+        assert!(fs::read_to_string(dir.join("users.jsonl")).unwrap().contains("{\"id\":2,\"name\":\"bob\",\"data\":\"0x62\"}\n"));
+        fs::remove_dir_all(dir).unwrap();
+
+        // other columns can not go under that header
+        let dir = env::temp_dir().join("sql-split-convert-columns-changed");
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.sql");
+        for insert in ["INSERT INTO `users` (`id`, `name`) VALUES (5,'erin');", "INSERT INTO `users` (`id`, `id`, `name`) VALUES (5,5,'erin');"] {
+            fs::write(&input, format!("{}\n{}\n", fs::read_to_string(path).unwrap(), insert)).unwrap();
+            let mut converter = Converter::new(Format::Csv, Dialect::Mysql, None, dir.clone());
+            let mut parser = Parser::from_file(fs::File::open(&input).unwrap(), true, Dialect::Mysql);
+            match converter.run(&mut parser) {
+                Err(ConvertErr::Syntax(e)) => assert_eq!(e.text, "Columns of the INSERT are not the ones of the first INSERT into its table."),
+                _ => panic!("changed columns were not reported"),
+            }
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn charsets(){
+        let charsets = |path: &str| {
+            let dir = env::temp_dir().join("sql-split-convert-charsets");
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let mut converter = Converter::new(Format::Csv, Dialect::Mysql, None, dir.clone());
+            let mut parser = Parser::from_file(fs::File::open(path).unwrap(), true, Dialect::Mysql);
+            if converter.run(&mut parser).is_err() {
+                panic!("convert failed");
+            }
+            converter.close().unwrap();
+            fs::remove_dir_all(&dir).unwrap();
+            converter.tables().into_iter().map(|table| (table.name, table.charset)).collect::<Vec<_>>()
+        };
+
+        // SET NAMES is the encoding of the whole dump
+        assert_eq!(charsets("../resources/test_db/oversized.sql")[0], (String::from("t1"), Some(String::from("utf8"))));
+        assert!(charsets("../resources/test_db/mysqldump.sql").iter().all(|(_, charset)| charset.as_deref() == Some("utf8mb4")));
+
+        // without it the DEFAULT CHARSET of the table, or nothing
+        assert_eq!(charsets("../resources/test_db/small.sql"), vec![
+            (String::from("hello"), Some(String::from("latin1"))),
+            (String::from("tokens"), None),
+        ]);
+    }
+
+    #[test]
+    fn sqlite_dump(){
+        // the backslash of 'C:\temp\' is no escape in SQLite
+        let dir = convert_as("../resources/test_db/sqlite.sql", Dialect::Sqlite, Format::Tsv, None, "sqlite");
+        let users = fs::read_to_string(dir.join("users.tsv")).unwrap();
+        assert!(users.contains("4\tC:\\\\temp\\\\\tdave@example.com\t\\N\n"), "{}", users);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn tsv(){
+        let dir = convert("../resources/test_db/preamble.sql", Format::Tsv, None, "tsv");
+        let users = fs::read_to_string(dir.join("users.tsv")).unwrap();
+        assert!(users.starts_with("id\tname\temail\n1\talice\talice@example.com\n"));
+        assert!(users.contains("3\tcarol\t\\N\n"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn split_by_size(){
+        let dir = convert("../resources/test_db/preamble.sql", Format::Csv, Some(60), "size");
+        let mut rows = 0;
+        let mut part = 1;
+        while let Ok(file) = fs::read_to_string(dir.join(format!("users.{}.csv", part))) {
+            // every file starts with the header
+            assert!(file.starts_with("id,name,email\n"));
+            assert!(file.len() <= 60 || file.lines().count() == 2);
+            rows += file.lines().count() - 1;
+            part += 1;
+        }
+        assert!(part > 2);
+        assert_eq!(rows, 6);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn jsonl(){
+        let dir = convert("../resources/test_db/mysqldump.sql", Format::Jsonl, None, "jsonl");
+        let users = fs::read_to_string(dir.join("users.jsonl")).unwrap();
+        let users: Vec<&str> = users.lines().collect();
+        assert_eq!(users.len(), 3);
+        assert_eq!(users[1], r#"{"id":2,"name":"bob","email":null,"created_at":"2023-01-02 11:30:00"}"#);
+        assert_eq!(users[2], r#"{"id":3,"name":"o'brien","email":"ob@example.com","created_at":"2023-01-03 09:15:00"}"#);
+
+        let orders = fs::read_to_string(dir.join("orders.jsonl")).unwrap();
+        assert!(orders.starts_with(r#"{"id":1,"user_id":1,"tenant_id":42,"total":19.99,"note":"first order"}"#));
+        assert!(orders.contains(r#""total":5.00,"note":null}"#));
+        for line in orders.lines() {
+            assert!(serde_json::from_str::<serde_json::Value>(line).is_ok());
+        }
+        fs::remove_dir_all(dir).unwrap();
+
+        for number in ["1", "-1.5e3", "0.99", "0", "1E+2"] {
+            assert!(is_number(number.as_bytes()), "{}", number);
+        }
+        for literal in ["0x1F", ".5", "007", "1.", "-", "CURRENT_TIMESTAMP", "1e"] {
+            assert!(!is_number(literal.as_bytes()), "{}", literal);
+        }
+    }
+}
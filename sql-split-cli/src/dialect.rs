@@ -0,0 +1,187 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+// flavour of SQL the input dump is written in
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Dialect {
+    #[default]
+    Mysql,
+    // pg_dump in plain format
+    Postgres,
+    // `sqlite3 db .dump`
+    Sqlite,
+    // SQL Server scripts, batches separated by GO lines
+    Mssql,
+    // standard SQL, no vendor extensions
+    Ansi,
+}
+
+// what the Tokenizer and the Parser have to know about a dialect
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Syntax {
+    // \' inside a string is a quote. otherwise only '' is.
+    pub backslash_escapes: bool,
+    // opening and closing quote of identifiers, e.g. `name` or [name]
+    pub identifier_quotes: &'static [(u8, u8)],
+    // N'text' strings
+    pub national_strings: bool,
+    // E'text' strings take backslash escapes
+    pub escape_strings: bool,
+    // $$text$$ and $tag$text$tag$ strings
+    pub dollar_quotes: bool,
+    // `# comment` up to the end of the line
+    pub hash_comments: bool,
+    // /*!40101 SET NAMES utf8 */ is run by MySQL rather than ignored
+    pub conditional_comments: bool,
+    // /* outer /* inner */ still a comment */
+    pub nested_comments: bool,
+    // the mysql client command DELIMITER changes what ends a statement
+    pub delimiter_command: bool,
+    // psql meta-commands such as \connect run up to the end of their line
+    pub backslash_commands: bool,
+    // COPY ... FROM stdin; is followed by data lines up to `\.`
+    pub copy_data: bool,
+    // statements may go without `;`, a line with GO ends the batch
+    pub batches: bool,
+}
+
+const MYSQL: Syntax = Syntax {
+    backslash_escapes: true,
+    identifier_quotes: &[(b'`', b'`')],
+    national_strings: false,
+    escape_strings: false,
+    dollar_quotes: false,
+    hash_comments: true,
+    conditional_comments: true,
+    nested_comments: false,
+    delimiter_command: true,
+    backslash_commands: false,
+    copy_data: false,
+    batches: false,
+};
+
+const POSTGRES: Syntax = Syntax {
+    backslash_escapes: false,
+    identifier_quotes: &[(b'"', b'"')],
+    national_strings: false,
+    escape_strings: true,
+    dollar_quotes: true,
+    hash_comments: false,
+    conditional_comments: false,
+    nested_comments: true,
+    delimiter_command: false,
+    backslash_commands: true,
+    copy_data: true,
+    batches: false,
+};
+
+// SQLite takes the quotes of every other database
+const SQLITE: Syntax = Syntax {
+    backslash_escapes: false,
+    identifier_quotes: &[(b'"', b'"'), (b'`', b'`'), (b'[', b']')],
+    national_strings: false,
+    escape_strings: false,
+    dollar_quotes: false,
+    hash_comments: false,
+    conditional_comments: false,
+    nested_comments: false,
+    delimiter_command: false,
+    backslash_commands: false,
+    copy_data: false,
+    batches: false,
+};
+
+const MSSQL: Syntax = Syntax {
+    backslash_escapes: false,
+    identifier_quotes: &[(b'"', b'"'), (b'[', b']')],
+    national_strings: true,
+    escape_strings: false,
+    dollar_quotes: false,
+    hash_comments: false,
+    conditional_comments: false,
+    nested_comments: true,
+    delimiter_command: false,
+    backslash_commands: false,
+    copy_data: false,
+    batches: true,
+};
+
+const ANSI: Syntax = Syntax {
+    backslash_escapes: false,
+    identifier_quotes: &[(b'"', b'"')],
+    national_strings: true,
+    escape_strings: false,
+    dollar_quotes: false,
+    hash_comments: false,
+    conditional_comments: false,
+    nested_comments: true,
+    delimiter_command: false,
+    backslash_commands: false,
+    copy_data: false,
+    batches: false,
+};
+
+// how much of the dump --dialect auto looks at
+const HEADER_SIZE: usize = 64 * 1024;
+
+impl Dialect {
+    pub fn syntax(&self) -> Syntax {
+        match self {
+            Dialect::Mysql => MYSQL,
+            Dialect::Postgres => POSTGRES,
+            Dialect::Sqlite => SQLITE,
+            Dialect::Mssql => MSSQL,
+            Dialect::Ansi => ANSI,
+        }
+    }
+
+    // guesses the dialect from what the dump tools write at the top of a
+    // dump. anything else is read as MySQL.
+    pub fn detect(path: &Path) -> io::Result<Dialect> {
+        let mut header = vec![];
+        File::open(path)?.take(HEADER_SIZE as u64).read_to_end(&mut header)?;
+        Ok(Self::from_header(&String::from_utf8_lossy(&header)))
+    }
+
+    fn from_header(header: &str) -> Dialect {
+        let has = |needle: &str| header.contains(needle);
+        let go_line = header.lines().any(|line| line.trim().eq_ignore_ascii_case("go"));
+
+        if has("-- MySQL dump") || has("-- MariaDB dump") || has("/*!40101") {
+            Dialect::Mysql
+        }else if has("-- PostgreSQL database dump") || has("SET standard_conforming_strings") || has(" FROM stdin;") {
+            Dialect::Postgres
+        }else if header.starts_with("PRAGMA foreign_keys=OFF;") || has("\nBEGIN TRANSACTION;\nCREATE TABLE") {
+            Dialect::Sqlite
+        }else if go_line || has("SET ANSI_NULLS ON") || has("[dbo].") {
+            Dialect::Mssql
+        }else{
+            Dialect::Mysql
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod dialect_test{
+    use std::path::Path;
+    use super::Dialect;
+
+    #[test]
+    fn detect(){
+        for (path, dialect) in [
+            ("../resources/test_db/mysqldump.sql", Dialect::Mysql),
+            ("../resources/test_db/sqlite.sql", Dialect::Sqlite),
+            ("../resources/test_db/mssql.sql", Dialect::Mssql),
+            ("../resources/test_db/postgres.sql", Dialect::Postgres),
+            ("../resources/test_db/copy.sql", Dialect::Postgres),
+            ("../resources/test_db/small.sql", Dialect::Mysql),
+        ] {
+            assert_eq!(Dialect::detect(Path::new(path)).unwrap(), dialect, "{}", path);
+        }
+
+        assert_eq!(Dialect::from_header("--\n-- PostgreSQL database dump\n--\n"), Dialect::Postgres);
+        assert!(Dialect::detect(Path::new("../resources/test_db/missing.sql")).is_err());
+    }
+}
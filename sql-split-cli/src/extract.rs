@@ -0,0 +1,184 @@
+use std::io::{self, Write};
+
+use crate::parser::parser::{StreamSource, TokenStream};
+use crate::parser::statement::{table_name, StatementKind};
+use crate::tokenizer::token_err::TokenErr;
+
+pub enum ExtractErr {
+    Syntax(TokenErr),
+    Io(io::Error),
+}
+
+impl From<io::Error> for ExtractErr {
+    fn from(e: io::Error) -> Self {
+        ExtractErr::Io(e)
+    }
+}
+
+// pulls a single table out of a dump: its DDL, LOCK/UNLOCK statements
+// and INSERTs together with the SET statements the dump starts with.
+pub struct Extractor {
+    table: String,
+    stop_early: bool,
+    in_preamble: bool,
+    // last statement was written, so are the line feeds after it
+    keeping: bool,
+    // inside an INSERT of the table, values tuples belong to it
+    inserting: bool,
+    locked: bool,
+    seen_data: bool,
+}
+
+impl Extractor {
+    pub fn new(table: &str, stop_early: bool) -> Self {
+        Self {
+            table: table.to_string(),
+            stop_early,
+            in_preamble: true,
+            keeping: false,
+            inserting: false,
+            locked: false,
+            seen_data: false,
+        }
+    }
+
+    // `db.users` matches `users` as well
+    fn matches(&self, name: &str) -> bool {
+        name == self.table || name.strip_suffix(&self.table).is_some_and(|db| db.ends_with('.'))
+    }
+
+    // true once the table is complete and the rest can be skipped
+    fn done(&self, name: &Option<String>) -> bool {
+        self.stop_early && self.seen_data && !self.locked &&
+            name.as_ref().is_some_and(|name| !self.matches(name))
+    }
+
+    pub fn run(&mut self, source: &mut dyn StreamSource, output: &mut dyn Write) -> Result<(), ExtractErr> {
+        while let Some(item) = source.token_stream().map_err(ExtractErr::Syntax)? {
+            match item {
+                TokenStream::Insert(tokens, insert_stmt) => {
+                    self.in_preamble = false;
+                    let name = table_name(&insert_stmt);
+                    if self.done(&name) {
+                        break;
+                    }
+
+                    self.inserting = name.as_ref().is_some_and(|name| self.matches(name));
+                    self.keeping = self.inserting;
+                    if self.inserting {
+                        self.seen_data = true;
+                        output.write_all(&tokens)?;
+                    }
+                },
+                TokenStream::ValuesTuple(tokens) => {
+                    self.keeping = self.inserting;
+                    if self.inserting {
+                        output.write_all(&tokens)?;
+                    }
+                },
+                TokenStream::Block(tokens, kind) => {
+                    let name = kind.table().map(String::from);
+                    if self.done(&name) {
+                        break;
+                    }
+
+                    self.inserting = false;
+                    self.keeping = if self.in_preamble && kind == StatementKind::Set {
+                        true
+                    }else if kind == StatementKind::UnlockTables {
+                        std::mem::replace(&mut self.locked, false)
+                    }else{
+                        self.in_preamble = false;
+                        let keep = name.as_ref().is_some_and(|name| self.matches(name));
+                        if keep && matches!(kind, StatementKind::LockTables(_)) {
+                            self.locked = true;
+                        }
+                        keep
+                    };
+
+                    if self.keeping {
+                        output.write_all(&tokens)?;
+                    }
+                },
+                TokenStream::SpaceOrLineFeed(tokens) => {
+                    if self.keeping {
+                        output.write_all(&tokens)?;
+                    }
+                },
+                // comments are dropped along with the `;` or line feed after them
+                TokenStream::Comment(_) => self.keeping = false,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod extract_test{
+    use std::fs::File;
+    use crate::dialect::Dialect;
+    use crate::parser::parser::{Parser, StreamSource};
+
+    use super::Extractor;
+
+    fn extract(path: &str, table: &str, stop_early: bool) -> String {
+        let mut parser = Parser::from_file(File::open(path).unwrap(), true, Dialect::Mysql);
+        let mut output = vec![];
+        if Extractor::new(table, stop_early).run(&mut parser, &mut output).is_err() {
+            panic!("extract failed");
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn single_table(){
+        let output = extract("../resources/test_db/small.sql", "hello", false);
+        assert!(output.starts_with("CREATE TABLE `hello` ("));
+        assert!(output.ends_with("INSERT INTO `hello` VALUES (2, 35, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1);\n\n"));
+        assert_eq!(output.matches("INSERT INTO").count(), 2);
+        assert!(!output.contains("access_tokens"));
+        assert!(!output.contains("SET FOREIGN_KEY_CHECKS"));
+
+        let output = extract("../resources/test_db/small.sql", "tokens", false);
+        assert!(output.starts_with("INSERT INTO `tokens`"));
+        assert!(output.ends_with("(2, 1, '', '', '');"));
+    }
+
+    #[test]
+    fn keeps_preamble(){
+        let output = extract("../resources/test_db/preamble.sql", "users", false);
+        assert!(output.starts_with("SET SQL_MODE = \"NO_AUTO_VALUE_ON_ZERO\";\nSET time_zone = \"+00:00\";\n"));
+        assert!(output.contains("CREATE TABLE `users`"));
+        assert_eq!(output.matches("\n(").count(), 6);
+        // not part of the preamble
+        assert!(!output.contains("FOREIGN_KEY_CHECKS"));
+    }
+
+    #[test]
+    fn lock_tables(){
+        let output = extract("../resources/test_db/mysqldump.sql", "users", false);
+        assert!(output.contains("DROP TABLE IF EXISTS `users`;"));
+        assert!(output.contains("LOCK TABLES `users` WRITE;"));
+        assert_eq!(output.matches("UNLOCK TABLES;").count(), 1);
+        assert!(!output.contains("orders"));
+    }
+
+    #[test]
+    fn stop_early(){
+        let path = "../resources/test_db/mysqldump.sql";
+        let mut parser = Parser::from_file(File::open(path).unwrap(), true, Dialect::Mysql);
+        let mut early = vec![];
+        if Extractor::new("users", true).run(&mut parser, &mut early).is_err() {
+            panic!("extract failed");
+        }
+
+        // stopped at the DROP TABLE of `orders`
+        let input = std::fs::read(path).unwrap();
+        let needle = b"DROP TABLE IF EXISTS `orders`;";
+        let orders = input.windows(needle.len()).position(|window| window == needle).unwrap();
+        assert_eq!(StreamSource::position(&parser), orders + needle.len());
+        assert_eq!(String::from_utf8(early).unwrap(), extract(path, "users", false));
+    }
+}
@@ -0,0 +1,256 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str;
+
+use crate::dialect::Dialect;
+use crate::parser::statement::{insert_columns, row, table_name};
+use crate::parser::table::CreateTable;
+use crate::parser::values::{parse_tuple, Value};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+// longer operators first so `<=` is not read as `<`
+const OPS: [(&str, Op); 7] = [
+    ("<=", Op::Le),
+    (">=", Op::Ge),
+    ("!=", Op::Ne),
+    ("<>", Op::Ne),
+    ("=", Op::Eq),
+    ("<", Op::Lt),
+    (">", Op::Gt),
+];
+
+impl Op {
+    fn matches(&self, ordering: Ordering) -> bool {
+        match self {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+// `orders: tenant_id = 42` of --where
+#[derive(Debug, PartialEq, Clone)]
+pub struct Condition {
+    pub table: String,
+    pub column: String,
+    pub op: Op,
+    // without the quotes of a 'quoted' value
+    pub value: String,
+}
+
+// `shop`.`orders` is written `shop.orders` like in table_name
+fn unquote(name: &str) -> String {
+    name.trim().replace(['`', '"', '[', ']'], "")
+}
+
+impl Condition {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let invalid = || format!("where `{}` should look like `table: column = value`", expr);
+        let (table, condition) = expr.split_once(':').ok_or_else(invalid)?;
+        let condition = condition.trim_start();
+
+        let end = condition.find(|c: char| c.is_whitespace() || "=!<>".contains(c)).ok_or_else(invalid)?;
+        let (column, rest) = condition.split_at(end);
+        let rest = rest.trim_start();
+        let (op_text, op) = OPS.iter().find(|(op, _)| rest.starts_with(op)).ok_or_else(invalid)?;
+
+        let value = rest[op_text.len()..].trim();
+        let value = match value.strip_prefix('\'').and_then(|value| value.strip_suffix('\'')) {
+            Some(quoted) => quoted.replace("''", "'"),
+            None => value.to_string(),
+        };
+
+        let (table, column) = (unquote(table), unquote(column));
+        if table.is_empty() || column.is_empty() || (value.is_empty() && !rest.ends_with('\'')) {
+            return Err(invalid())
+        }
+        Ok(Self { table, column, op: *op, value })
+    }
+
+    // `orders` also applies to `shop.orders`
+    fn applies(&self, table: &str) -> bool {
+        table == self.table || table.rsplit_once('.').is_some_and(|(_, name)| name == self.table)
+    }
+
+    fn test(&self, value: &Value) -> bool {
+        ordering(value, &self.value).is_some_and(|ordering| self.op.matches(ordering))
+    }
+}
+
+fn number(bytes: &[u8]) -> Option<f64> {
+    str::from_utf8(bytes).ok()?.trim().parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+// numbers are compared as numbers, everything else byte by byte. NULL
+// compares to nothing, like in SQL.
+fn ordering(value: &Value, expected: &str) -> Option<Ordering> {
+    let bytes: Cow<[u8]> = match value {
+        Value::Null => return None,
+        Value::Int(int) => match expected.parse::<i64>() {
+            Ok(expected) => return Some(int.cmp(&expected)),
+            Err(_) => Cow::Owned(int.to_string().into_bytes()),
+        },
+        Value::Decimal(bytes) | Value::Expr(bytes) => Cow::Borrowed(*bytes),
+        Value::String(bytes) | Value::Blob(bytes) => Cow::Borrowed(bytes.as_ref()),
+    };
+
+    match (number(&bytes), number(expected.as_bytes())) {
+        (Some(number), Some(expected)) => number.partial_cmp(&expected),
+        _ => Some(bytes.as_ref().cmp(expected.as_bytes())),
+    }
+}
+
+// drops the rows of an INSERT that do not match every condition on its
+// table
+pub struct RowFilter {
+    conditions: Vec<Condition>,
+    // strings of the input have backslash escapes
+    backslash_escapes: bool,
+    // columns of the CREATE TABLE statements seen so far
+    columns: HashMap<String, Vec<String>>,
+    // INSERT the positions below were looked up for
+    header: Vec<u8>,
+    // (condition, position of its column in the tuple)
+    tests: Vec<(usize, usize)>,
+}
+
+impl RowFilter {
+    // None without conditions, nothing to filter
+    pub fn new(conditions: Vec<Condition>, dialect: Dialect) -> Option<Self> {
+        if conditions.is_empty() {
+            return None
+        }
+        Some(Self {
+            conditions,
+            backslash_escapes: dialect.syntax().backslash_escapes,
+            columns: HashMap::new(),
+            header: vec![],
+            tests: vec![],
+        })
+    }
+
+    // columns of the CREATE TABLE statements seen so far, by table
+    pub fn columns(&self) -> &HashMap<String, Vec<String>> {
+        &self.columns
+    }
+
+    // a resumed split starts with the columns of the tables split before
+    pub fn set_columns(&mut self, columns: HashMap<String, Vec<String>>) {
+        self.columns = columns;
+        self.header.clear();
+    }
+
+    pub fn create_table(&mut self, table: &str, tokens: &[u8]) {
+        if self.conditions.iter().any(|condition| condition.applies(table)) {
+            let columns = CreateTable::parse(tokens).map(|create| create.column_names()).unwrap_or_default();
+            self.columns.insert(table.to_string(), columns);
+            // the same INSERT header may have other columns now
+            self.header.clear();
+        }
+    }
+
+    // the columns of an INSERT without a column list are the ones of
+    // its CREATE TABLE
+    fn lookup(&mut self, header: &[u8]) -> Result<(), &'static str> {
+        let table = table_name(header).unwrap_or_default();
+        let mut tests = vec![];
+        for (index, condition) in self.conditions.iter().enumerate().filter(|(_, condition)| condition.applies(&table)) {
+            let columns = insert_columns(header).or_else(|| self.columns.get(&table).cloned())
+                .ok_or("Columns of the --where table are unknown, its CREATE TABLE is not in the dump.")?;
+            let position = columns.iter().position(|column| column.eq_ignore_ascii_case(&condition.column))
+                .ok_or("Column of --where is not in the INSERT or CREATE TABLE of its table.")?;
+            tests.push((index, position));
+        }
+
+        self.header = header.to_vec();
+        self.tests = tests;
+        Ok(())
+    }
+
+    // `tuple` is a row of the INSERT `header`, separators may be around it
+    pub fn keep(&mut self, header: &[u8], tuple: &[u8]) -> Result<bool, &'static str> {
+        if header != self.header {
+            self.lookup(header)?;
+        }
+        if self.tests.is_empty() {
+            return Ok(true)
+        }
+
+        let values = parse_tuple(row(tuple), self.backslash_escapes)?;
+        for (condition, position) in &self.tests {
+            let value = values.get(*position).ok_or("Values tuple has fewer values than the columns of --where.")?;
+            if !self.conditions[*condition].test(value) {
+                return Ok(false)
+            }
+        }
+        Ok(true)
+    }
+}
+
+
+#[cfg(test)]
+mod filter_test{
+    use crate::dialect::Dialect;
+
+    use super::{Condition, Op, RowFilter};
+
+    #[test]
+    fn parse(){
+        let condition = Condition::parse("orders: tenant_id = 42").unwrap();
+        assert_eq!(condition, Condition {
+            table: String::from("orders"),
+            column: String::from("tenant_id"),
+            op: Op::Eq,
+            value: String::from("42"),
+        });
+
+        let condition = Condition::parse("`shop`.`users`:`name`<>'o''brien'").unwrap();
+        assert_eq!((condition.table.as_str(), condition.column.as_str()), ("shop.users", "name"));
+        assert_eq!((condition.op, condition.value.as_str()), (Op::Ne, "o'brien"));
+        assert_eq!(Condition::parse("t: total >= 1.5").unwrap().op, Op::Ge);
+        assert_eq!(Condition::parse("t: note = ''").unwrap().value, "");
+
+        for invalid in ["tenant_id = 42", "orders: tenant_id 42", "orders: tenant_id =", ": id = 1", "orders: = 1"] {
+            assert!(Condition::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn keep(){
+        let conditions = ["orders: tenant_id = 42", "orders: total < 100", "orders: note != 'skip'"];
+        let mut filter = RowFilter::new(conditions.iter().map(|expr| Condition::parse(expr).unwrap()).collect(), Dialect::Mysql).unwrap();
+        filter.create_table("shop.orders", b"CREATE TABLE `shop`.`orders` (`id` int, `tenant_id` int, `total` decimal(10,2), `note` text)");
+
+        let header = b"INSERT INTO `shop`.`orders` VALUES ";
+        assert_eq!(filter.keep(header, b"(1,42,19.99,'first'),"), Ok(true));
+        assert_eq!(filter.keep(header, b"(2,7,5.00,NULL),"), Ok(false));
+        assert_eq!(filter.keep(header, b"(3,42,120.50,'gift')"), Ok(false));
+        assert_eq!(filter.keep(header, b"(4,42,0.99,'skip');"), Ok(false));
+        // NULL matches no condition, not even !=
+        assert_eq!(filter.keep(header, b"(5,42,0.99,NULL);"), Ok(false));
+
+        // the column list of the INSERT wins over CREATE TABLE
+        let header = b"INSERT INTO `orders` (`note`, `total`, `tenant_id`) VALUES ";
+        assert_eq!(filter.keep(header, b"('x', '9.5', 42)"), Ok(true));
+        assert_eq!(filter.keep(header, b"('x', 9.5, 41)"), Ok(false));
+
+        // other tables are not filtered
+        assert_eq!(filter.keep(b"INSERT INTO `users` VALUES ", b"(1)"), Ok(true));
+        assert!(filter.keep(b"INSERT INTO `orders` VALUES ", b"(1,42,1,'')").is_err());
+        assert!(filter.keep(b"INSERT INTO `orders` (`id`) VALUES ", b"(1)").is_err());
+    }
+}
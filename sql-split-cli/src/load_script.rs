@@ -0,0 +1,170 @@
+use crate::convert::{ConvertedTable, Format};
+
+pub const LOAD_SCRIPT_FILE: &str = "./load.sql";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Mysql,
+    Postgres,
+}
+
+// `db.users` is quoted as `db`.`users`
+fn quote(target: Target, name: &str) -> String {
+    let (open, close) = match target {
+        Target::Mysql => ('`', '`'),
+        Target::Postgres => ('"', '"'),
+    };
+    name.split('.')
+        .map(|part| format!("{}{}{}", open, part.replace(close, &format!("{}{}", close, close)), close))
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+fn column_list(target: Target, columns: &[String]) -> String {
+    if columns.is_empty() {
+        return String::new()
+    }
+    let columns: Vec<String> = columns.iter().map(|column| quote(target, column)).collect();
+    format!(" ({})", columns.join(", "))
+}
+
+// mysql reads the tsv files with the LOAD DATA defaults, \N is NULL, and
+// an unquoted NULL of the csv files is NULL too. binary columns are read
+// into a variable and unhexed from \x1F. without a charset from the dump
+// the server's character_set_database is used.
+fn mysql(table: &ConvertedTable, file: &str, format: Format) -> String {
+    let fields = match format {
+        Format::Csv => "FIELDS TERMINATED BY ',' OPTIONALLY ENCLOSED BY '\"' ESCAPED BY ''",
+        _ => "FIELDS TERMINATED BY '\\t' ENCLOSED BY '' ESCAPED BY '\\\\'",
+    };
+    let ignore = if table.columns.is_empty() { "" } else { "\nIGNORE 1 LINES" };
+
+    let mut columns = vec![];
+    let mut set = vec![];
+    for (index, column) in table.columns.iter().enumerate() {
+        let column = quote(Target::Mysql, column);
+        if table.binary.get(index) == Some(&true) {
+            columns.push(format!("@{}", column));
+            set.push(format!("{} = UNHEX(SUBSTRING(@{}, 3))", column, column));
+        }else{
+            columns.push(column);
+        }
+    }
+    let columns = if columns.is_empty() { String::new() } else { format!(" ({})", columns.join(", ")) };
+    let set = if set.is_empty() { String::new() } else { format!("\nSET {}", set.join(", ")) };
+    let charset = table.charset.as_ref().map_or(String::new(), |charset| format!("\nCHARACTER SET {}", charset));
+
+    format!(
+        "LOAD DATA LOCAL INFILE '{}' INTO TABLE {}{}\n{}\nLINES TERMINATED BY '\\n'{}{}{};\n",
+        file, quote(Target::Mysql, &table.name), charset, fields, ignore, columns, set
+    )
+}
+
+// \copy has to fit on one line
+fn postgres(table: &ConvertedTable, file: &str, format: Format) -> String {
+    let options = match (format, table.columns.is_empty()) {
+        (Format::Csv, false) => "(FORMAT csv, HEADER true, NULL 'NULL')",
+        (Format::Csv, true) => "(FORMAT csv, NULL 'NULL')",
+        // text format reads \N as NULL, HEADER needs postgres 15
+        (_, false) => "(FORMAT text, HEADER true)",
+        (_, true) => "(FORMAT text)",
+    };
+    format!(
+        "\\copy {}{} FROM '{}' {}\n",
+        quote(Target::Postgres, &table.name), column_list(Target::Postgres, &table.columns), file, options
+    )
+}
+
+// loads every converted file, table by table. the files are named
+// relative to the script so the directory can be moved as a whole.
+pub fn script(target: Target, format: Format, tables: &[ConvertedTable]) -> Vec<u8> {
+    let mut script = String::new();
+    if target == Target::Mysql {
+        script.push_str("SET FOREIGN_KEY_CHECKS=0;\n");
+    }
+
+    for table in tables {
+        script.push_str(&format!("\n-- {}\n", table.name));
+        for file in &table.files {
+            let file = file.file_name().unwrap_or_default().to_string_lossy().replace('\'', "''");
+            script.push_str(&match target {
+                Target::Mysql => mysql(table, &file, format),
+                Target::Postgres => postgres(table, &file, format),
+            });
+        }
+    }
+
+    if target == Target::Mysql {
+        script.push_str("\nSET FOREIGN_KEY_CHECKS=1;\n");
+    }
+    script.into_bytes()
+}
+
+
+#[cfg(test)]
+mod load_script_test{
+    use std::path::PathBuf;
+    use crate::convert::{ConvertedTable, Format};
+
+    use super::{script, Target};
+
+    #[test]
+    fn scripts(){
+        let tables = [
+            ConvertedTable {
+                name: String::from("shop.users"),
+                columns: vec![String::from("id"), String::from("name")],
+                binary: vec![false, false],
+                charset: Some(String::from("utf8mb4")),
+                files: vec![PathBuf::from("./shop.users.1.csv"), PathBuf::from("./shop.users.2.csv")],
+            },
+            ConvertedTable {
+                name: String::from("logs"),
+                columns: vec![],
+                binary: vec![],
+                charset: None,
+                files: vec![PathBuf::from("./logs.1.csv")],
+            },
+        ];
+
+        let mysql = String::from_utf8(script(Target::Mysql, Format::Csv, &tables)).unwrap();
+        assert!(mysql.contains("\
+            LOAD DATA LOCAL INFILE 'shop.users.2.csv' INTO TABLE `shop`.`users`\n\
+            CHARACTER SET utf8mb4\n\
+            FIELDS TERMINATED BY ',' OPTIONALLY ENCLOSED BY '\"' ESCAPED BY ''\n\
+            LINES TERMINATED BY '\\n'\n\
+            IGNORE 1 LINES (`id`, `name`);\n"));
+        // the server default when the dump does not tell
+        assert!(mysql.contains("INTO TABLE `logs`\nFIELDS"));
+        assert!(mysql.contains("LINES TERMINATED BY '\\n';\n"));
+        assert_eq!(mysql.matches("LOAD DATA").count(), 3);
+
+        let postgres = String::from_utf8(script(Target::Postgres, Format::Csv, &tables)).unwrap();
+        assert!(postgres.contains("\\copy \"shop\".\"users\" (\"id\", \"name\") FROM 'shop.users.1.csv' (FORMAT csv, HEADER true, NULL 'NULL')\n"));
+        assert!(postgres.contains("\\copy \"logs\" FROM 'logs.1.csv' (FORMAT csv, NULL 'NULL')\n"));
+
+        let postgres = String::from_utf8(script(Target::Postgres, Format::Tsv, &tables)).unwrap();
+        assert!(postgres.contains("FROM 'shop.users.1.csv' (FORMAT text, HEADER true)\n"));
+    }
+
+    #[test]
+    fn binary_columns(){
+        let tables = [ConvertedTable {
+            name: String::from("files"),
+            columns: vec![String::from("id"), String::from("data"), String::from("hash")],
+            binary: vec![false, true, true],
+            charset: None,
+            files: vec![PathBuf::from("./files.tsv")],
+        }];
+
+        // the hex of binary columns goes through a variable
+        let mysql = String::from_utf8(script(Target::Mysql, Format::Tsv, &tables)).unwrap();
+        assert!(mysql.contains("\
+            IGNORE 1 LINES (`id`, @`data`, @`hash`)\n\
+            SET `data` = UNHEX(SUBSTRING(@`data`, 3)), `hash` = UNHEX(SUBSTRING(@`hash`, 3));\n"));
+
+        // COPY reads \x1F into bytea as it is
+        let postgres = String::from_utf8(script(Target::Postgres, Format::Tsv, &tables)).unwrap();
+        assert!(postgres.contains("\\copy \"files\" (\"id\", \"data\", \"hash\") FROM 'files.tsv' (FORMAT text, HEADER true)\n"));
+    }
+}
@@ -1,69 +1,296 @@
 #[macro_use] 
 extern crate clap;
 
+mod dialect;
 mod tokenizer;
 mod parser;
 mod splitter;
+mod parallel;
+mod checkpoint;
+mod writer;
+mod manifest;
+mod stats;
+mod extract;
+mod validate;
+mod rejected;
+mod merge;
+mod verify;
+mod convert;
+mod load_script;
+mod translate;
+mod filter;
 mod cli;
 
 use std::str;
 use std::process;
-use std::fs::File;
-use std::io::prelude::*;
-use splitter::SplitterSettings;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use checkpoint::{Checkpoint, CHECKPOINT_FILE};
+use dialect::Dialect;
+use manifest::{Manifest, MANIFEST_FILE};
+use splitter::{OnError, SplitterSettings};
 use splitter::SplitterState;
 use splitter::Splitter;
+use writer::{ChunkWriter, WrittenChunk};
+use parser::parser::Parser;
+use stats::Stats;
+use extract::{Extractor, ExtractErr};
+use validate::Validator;
+use rejected::{Rejected, REJECTED_FILE};
+use merge::{Merger, MergeErr};
+use convert::{Converter, ConvertErr};
+use load_script::LOAD_SCRIPT_FILE;
 
 fn log_error(err: &str) -> ! {
     eprintln!("{}", err);
-    process::exit(0)
+    process::exit(1)
 }
 
-fn create_file(name: usize) -> File {
-    let file_name = format!("./{:?}.sql", name);
-    File::create(file_name).unwrap()
+fn load_checkpoint(input_size: usize) -> Checkpoint {
+    let checkpoint = match Checkpoint::load(Path::new(CHECKPOINT_FILE)) {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => log_error(e.as_str()),
+    };
+
+    if checkpoint.input_size != input_size {
+        log_error("checkpoint was written for a different input file")
+    }
+
+    checkpoint
 }
 
+fn record_chunk(manifest: &mut Manifest, splitter: &mut Splitter, written: WrittenChunk) {
+    if let Some(stats) = splitter.completed_chunk() {
+        manifest.record(written, stats);
+    }
 
-fn main(){
-    let (file, write_buffer) = cli::args();
+    if let Err(e) = manifest.save(Path::new(MANIFEST_FILE)) {
+        log_error(e.as_str())
+    }
+}
 
-    let file = match file {
-        Ok(file) => file,
-        Err(e) => log_error(e),
+fn stats(args: cli::StatsArgs) {
+    let mut parser = Parser::from_file(args.file, true, args.dialect);
+    let stats = match Stats::collect(&mut parser) {
+        Ok(stats) => stats,
+        Err(e) => log_error(e.to_string().as_str()),
     };
 
-    let write_buffer = match write_buffer {
-        Ok(file) => file,
+    if args.json {
+        println!("{}", stats.json());
+    }else{
+        print!("{}", stats.text());
+    }
+}
+
+fn extract(args: cli::ExtractArgs) {
+    let mut parser = Parser::from_file(args.file, true, args.dialect);
+    let stdout = std::io::stdout();
+    let mut output = std::io::BufWriter::new(stdout.lock());
+    let result = Extractor::new(&args.table, args.stop_early).run(&mut parser, &mut output);
+    let result = result.and_then(|_| output.flush().map_err(ExtractErr::from));
+    match result {
+        Ok(_) => {},
+        Err(ExtractErr::Syntax(e)) => log_error(e.to_string().as_str()),
+        Err(ExtractErr::Io(e)) => log_error(e.to_string().as_str()),
+    }
+}
+
+fn validate(args: cli::ValidateArgs) {
+    let mut parser = Parser::from_file(args.file, true, args.dialect);
+    let problems = Validator::new().run(&mut parser);
+    for problem in &problems {
+        println!("byte {}: {}", problem.pos, problem.text);
+    }
+
+    if problems.is_empty() {
+        println!("no problems found");
+    }else{
+        let plural = if problems.len() == 1 { "" } else { "s" };
+        eprintln!("{} problem{} found", problems.len(), plural);
+        process::exit(1)
+    }
+}
+
+fn merge(args: cli::MergeArgs) {
+    let stdout = std::io::stdout();
+    let mut output = std::io::BufWriter::new(stdout.lock());
+    let mut merger = Merger::new(args.coalesce, args.dialect);
+    for chunk in &args.chunks {
+        match merger.chunk(chunk, &mut output) {
+            Ok(_) => {},
+            Err(MergeErr::Syntax(e)) => log_error(format!("{}: {}", chunk.display(), e).as_str()),
+            Err(MergeErr::Io(e)) => log_error(format!("{}: {}", chunk.display(), e).as_str()),
+        }
+    }
+
+    let result = merger.finish(&mut output).and_then(|_| output.flush().map_err(MergeErr::from));
+    match result {
+        Ok(_) => {},
+        Err(MergeErr::Syntax(e)) => log_error(e.to_string().as_str()),
+        Err(MergeErr::Io(e)) => log_error(e.to_string().as_str()),
+    }
+}
+
+fn convert(args: cli::ConvertArgs) {
+    let mut parser = Parser::from_file(args.file, true, args.dialect);
+    let mut converter = Converter::new(args.format, args.dialect, args.write, PathBuf::from("."));
+    let result = converter.run(&mut parser).and_then(|_| converter.close().map_err(ConvertErr::from));
+    match result {
+        Ok(_) => {},
+        Err(ConvertErr::Syntax(e)) => log_error(e.to_string().as_str()),
+        Err(ConvertErr::Io(e)) => log_error(e.to_string().as_str()),
+    }
+
+    let tables = converter.tables();
+    for file in tables.iter().flat_map(|table| &table.files) {
+        println!("{}", file.display());
+    }
+
+    if let Some(target) = args.load_script {
+        if let Err(e) = std::fs::write(LOAD_SCRIPT_FILE, load_script::script(target, args.format, &tables)) {
+            log_error(e.to_string().as_str())
+        }
+        println!("{}", LOAD_SCRIPT_FILE);
+    }
+}
+
+// parses the input and the chunks again and compares what they hold
+fn verify_split(input: &Path, manifest: &Manifest, preamble: &[u8], dialect: Dialect) {
+    let chunks: Vec<PathBuf> = manifest.chunks.iter().map(|chunk| PathBuf::from(&chunk.file)).collect();
+    let summaries = verify::summarize_input(input, dialect)
+        .and_then(|input| Ok((input, verify::summarize_chunks(&chunks, preamble, dialect)?)));
+    let (input, output) = match summaries {
+        Ok(summaries) => summaries,
         Err(e) => log_error(e.as_str()),
     };
 
+    let mismatches = verify::compare(&input, &output);
+    if mismatches.is_empty() {
+        eprintln!("verified {} rows and {} statements in {} chunks", input.rows, input.statements, chunks.len());
+    }else{
+        for mismatch in &mismatches {
+            eprintln!("verify failed: {}", mismatch);
+        }
+        process::exit(1)
+    }
+}
+
+fn split(args: cli::Args) {
+    let input_size = args.file.metadata().map(|meta| meta.len() as usize).unwrap_or(0);
+    let mut manifest = Manifest::new(&args.path, input_size);
+    let resume = if args.resume {
+        let checkpoint = load_checkpoint(input_size);
+        // keep the entries of the chunks that survived
+        if let Ok(existing) = Manifest::load(Path::new(MANIFEST_FILE)) {
+            manifest.chunks = existing.chunks.into_iter().take(checkpoint.chunks).collect();
+        }
+        Some(checkpoint)
+    }else{
+        None
+    };
+
+    let mut rejected = if args.on_error == OnError::Quarantine {
+        match Rejected::new(&args.path, args.resume) {
+            Ok(rejected) => Some(rejected),
+            Err(e) => log_error(e.to_string().as_str()),
+        }
+    }else{
+        None
+    };
+    let mut skipped = 0;
+
+    let mut writer = ChunkWriter::new(resume.as_ref().map_or(0, |checkpoint| checkpoint.chunks));
     let mut splitter = Splitter::new(SplitterSettings {
-        write: write_buffer,
-        file,
+        write: args.write,
+        file: args.file,
+        path: args.path.clone(),
+        fast_scan: args.fast_scan,
+        threads: args.threads,
+        segment_size: args.segment_size,
+        replay_preamble: args.replay_preamble,
+        resume,
+        on_error: args.on_error,
+        rows_per_insert: args.rows_per_insert,
+        dialect: args.dialect,
+        translate: args.translate,
+        chunk_transactions: args.chunk_transactions,
+        filter: args.filter,
     });
 
-    let mut file_count = 1;
-    let mut buffer = create_file(file_count);
-    let mut first_file = true;
-
     loop {
         match splitter.process() {
             SplitterState::Chunk(file_state, tokens) => {
-                if file_state == splitter::FileState::New {
-                    if first_file == true {
-                        first_file = false;
-                        continue;
+                match writer.write(&file_state, &tokens) {
+                    // previous chunk is safely on disk, remember how far we got
+                    Ok(Some(written)) => {
+                        record_chunk(&mut manifest, &mut splitter, written);
+                        if let Some(checkpoint) = splitter.completed_checkpoint() {
+                            if let Err(e) = checkpoint.save(Path::new(CHECKPOINT_FILE)) {
+                                log_error(e.as_str())
+                            }
+                        }
+                    },
+                    Ok(None) => {},
+                    Err(e) => log_error(e.to_string().as_str()),
+                }
+            },
+            SplitterState::SyntaxErr(e) => log_error(e.to_string().as_str()),
+            SplitterState::Skipped(e, start, end) => {
+                skipped += 1;
+                if let Some(rejected) = rejected.as_mut() {
+                    if let Err(e) = rejected.write(&e, start, end) {
+                        log_error(e.to_string().as_str())
                     }
-
-                    file_count += 1;
-                    buffer = create_file(file_count);
                 }
-
-                buffer.write_all(&tokens).unwrap();
             },
-            SplitterState::SyntaxErr(e) => log_error(e.text),
             SplitterState::Done => break,
         }
     }
+
+    match writer.close() {
+        Ok(Some(written)) => record_chunk(&mut manifest, &mut splitter, written),
+        Ok(None) => {},
+        Err(e) => log_error(e.to_string().as_str()),
+    }
+
+    if let Some(rejected) = rejected.as_mut() {
+        if let Err(e) = rejected.close() {
+            log_error(e.to_string().as_str())
+        }
+    }
+
+    if skipped > 0 {
+        eprintln!("skipped {} malformed statements", skipped);
+        if rejected.is_some() {
+            eprintln!("the skipped bytes are in {}", REJECTED_FILE);
+        }
+    }
+
+    let untranslated = splitter.untranslated();
+    for problem in untranslated {
+        eprintln!("byte {}: {} ({} times)", problem.pos, problem.text, problem.count);
+    }
+    if !untranslated.is_empty() {
+        eprintln!("{} kinds of statements could not be fully translated", untranslated.len());
+    }
+
+    // split is complete, nothing left to resume
+    let _ = std::fs::remove_file(CHECKPOINT_FILE);
+
+    if args.verify {
+        verify_split(&args.path, &manifest, splitter.replayed_preamble(), args.dialect);
+    }
+}
+
+fn main(){
+    match cli::command() {
+        Ok(cli::Command::Split(args)) => split(args),
+        Ok(cli::Command::Stats(args)) => stats(args),
+        Ok(cli::Command::Extract(args)) => extract(args),
+        Ok(cli::Command::Validate(args)) => validate(args),
+        Ok(cli::Command::Merge(args)) => merge(args),
+        Ok(cli::Command::Convert(args)) => convert(args),
+        Err(e) => log_error(e.as_str()),
+    }
 }
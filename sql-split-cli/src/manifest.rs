@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::splitter::ChunkStats;
+use crate::writer::WrittenChunk;
+
+pub const MANIFEST_FILE: &str = "./manifest.json";
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestChunk {
+    pub file: String,
+    pub bytes: usize,
+    pub sha256: String,
+    pub statements: usize,
+    pub rows: usize,
+    pub tables: Vec<String>,
+    // input bytes [input_start, input_end) the chunk was made from
+    pub input_start: usize,
+    pub input_end: usize,
+}
+
+// describes every chunk of a split so loaders can schedule and verify
+// the imports.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub input: String,
+    pub input_size: usize,
+    pub chunks: Vec<ManifestChunk>,
+}
+
+impl Manifest {
+    pub fn new(input: &Path, input_size: usize) -> Self {
+        Self {
+            input: input.to_string_lossy().into_owned(),
+            input_size,
+            chunks: vec![],
+        }
+    }
+
+    pub fn record(&mut self, written: WrittenChunk, stats: ChunkStats) {
+        self.chunks.push(ManifestChunk {
+            file: written.file,
+            bytes: written.bytes,
+            sha256: written.sha256,
+            statements: stats.statements,
+            rows: stats.rows,
+            tables: stats.tables.into_iter().collect(),
+            input_start: stats.input_start,
+            input_end: stats.input_end,
+        });
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_vec_pretty(self).map_err(|e| e.to_string())?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json).map_err(|e| format!("unable to write manifest: {}", e))?;
+        fs::rename(&tmp, path).map_err(|e| format!("unable to write manifest: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read(path).map_err(|_| format!("unable to read {}", path.display()))?;
+        serde_json::from_slice(&json).map_err(|e| format!("invalid manifest: {}", e))
+    }
+}
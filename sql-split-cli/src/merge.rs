@@ -0,0 +1,333 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use sql_split_reader::Reader;
+
+use crate::dialect::Dialect;
+use crate::parser::parser::{Parser, TokenStream};
+use crate::parser::statement::StatementKind;
+use crate::splitter::{CHUNK_INSERT, CHUNK_TRANSACTION};
+use crate::tokenizer::{
+    tokenizer::Tokenizer,
+    token_err::TokenErr,
+};
+
+// coalesced INSERTs stop growing at this size, mysqldump uses about the
+// same for --net-buffer-length
+pub const COALESCE_LIMIT: usize = 1024 * 1024;
+
+pub enum MergeErr {
+    Syntax(TokenErr),
+    Io(io::Error),
+}
+
+impl From<io::Error> for MergeErr {
+    fn from(e: io::Error) -> Self {
+        MergeErr::Io(e)
+    }
+}
+
+// stitches chunks back into one dump. the SET statements replayed at the
+// top of every chunk and the COMMIT and BEGIN of --chunk-transactions are
+// dropped, and an INSERT the splitter cut in two is joined again.
+pub struct Merger {
+    coalesce: bool,
+    dialect: Dialect,
+    chunks: usize,
+    // leading SET statements of the first chunk, as --replay-preamble
+    // writes them
+    preamble: Vec<u8>,
+    in_preamble: bool,
+    // nothing of the current chunk was read yet
+    chunk_start: bool,
+    // the next INSERT is a copy of the header of the one held open, the
+    // splitter cut it there
+    cut: bool,
+    // header of the INSERT being written
+    insert: Vec<u8>,
+    statement_len: usize,
+    // the last INSERT ended here, its `;` is held back in case the next
+    // statement continues it
+    open: bool,
+    // line feeds and comments not written yet, after the held back `;`
+    // they may be followed by the rest of the INSERT
+    held: Vec<u8>,
+    // items left of a statement --chunk-transactions added, the COMMIT or
+    // BEGIN and its line feed
+    skip: usize,
+}
+
+// parser over a chunk that starts right after the SET statements
+// --replay-preamble repeated at its top
+pub fn open_chunk(path: &Path, preamble: &[u8], dialect: Dialect) -> io::Result<Parser> {
+    let mut file = File::open(path)?;
+    let mut start = 0;
+    if !preamble.is_empty() {
+        let mut head = vec![0; preamble.len()];
+        if file.read_exact(&mut head).is_ok() && head == preamble {
+            start = head.len();
+        }
+    }
+
+    let mut reader = Reader::new(file);
+    reader.seek(start);
+    let mut tokenizer = Tokenizer::new(reader);
+    tokenizer.set_dialect(dialect);
+    Ok(Parser::new(tokenizer))
+}
+
+// values of an INSERT without the header
+fn rows<'a>(tokens: &'a [u8], header: &[u8]) -> &'a [u8] {
+    let rows = &tokens[(header.len() - 1).min(tokens.len())..];
+    rows.strip_prefix(b" ").unwrap_or(rows)
+}
+
+impl Merger {
+    pub fn new(coalesce: bool, dialect: Dialect) -> Self {
+        Self {
+            coalesce,
+            dialect,
+            chunks: 0,
+            preamble: vec![],
+            in_preamble: true,
+            chunk_start: true,
+            cut: false,
+            insert: vec![],
+            statement_len: 0,
+            open: false,
+            held: vec![],
+            skip: 0,
+        }
+    }
+
+    // writes the held back `;` and whatever came after it
+    fn flush(&mut self, output: &mut dyn Write) -> io::Result<()> {
+        if std::mem::replace(&mut self.open, false) {
+            output.write_all(b";")?;
+        }
+        output.write_all(&self.held)?;
+        self.held.clear();
+        Ok(())
+    }
+
+    // true when the INSERT continues the one held open
+    fn continues(&self, tokens: &[u8], header: &[u8], cut: bool) -> bool {
+        if !self.open || header != self.insert || !self.held.iter().all(u8::is_ascii_whitespace) {
+            return false
+        }
+        cut || (self.coalesce && self.statement_len + tokens.len() <= COALESCE_LIMIT)
+    }
+
+    // the last row of a statement ends with `;`
+    fn write_rows(&mut self, tokens: &[u8], output: &mut dyn Write) -> io::Result<()> {
+        self.statement_len += tokens.len();
+        match tokens.strip_suffix(b";") {
+            Some(tokens) => {
+                self.open = true;
+                output.write_all(tokens)
+            },
+            None => output.write_all(tokens),
+        }
+    }
+
+    fn item(&mut self, item: TokenStream, output: &mut dyn Write) -> io::Result<()> {
+        if self.skip > 0 {
+            self.skip -= 1;
+            return Ok(())
+        }
+        if matches!(&item, TokenStream::Comment(tokens) if tokens == CHUNK_TRANSACTION) {
+            // a COMMIT at the end of a chunk goes on a line of its own
+            if !self.chunk_start && self.held.last() == Some(&b'\n') {
+                self.held.pop();
+            }
+            self.skip = 2;
+            return Ok(())
+        }
+        if matches!(&item, TokenStream::Comment(tokens) if tokens == CHUNK_INSERT) {
+            self.cut = true;
+            return Ok(())
+        }
+
+        self.chunk_start = false;
+        let cut = std::mem::take(&mut self.cut);
+        match item {
+            TokenStream::Insert(tokens, header) => {
+                self.in_preamble = false;
+                if self.continues(&tokens, &header, cut) {
+                    self.open = false;
+                    output.write_all(b",")?;
+                    output.write_all(&std::mem::take(&mut self.held))?;
+                    return self.write_rows(rows(&tokens, &header), output)
+                }
+
+                self.flush(output)?;
+                self.insert = header;
+                self.statement_len = 0;
+                self.write_rows(&tokens, output)
+            },
+            TokenStream::ValuesTuple(tokens) => {
+                self.flush(output)?;
+                self.write_rows(&tokens, output)
+            },
+            TokenStream::Block(tokens, kind) => {
+                self.flush(output)?;
+                if self.in_preamble && self.chunks == 1 && kind == StatementKind::Set {
+                    self.preamble.extend(&tokens);
+                    self.preamble.push(b'\n');
+                }else{
+                    self.in_preamble = false;
+                }
+                output.write_all(&tokens)
+            },
+            TokenStream::Comment(tokens) |
+            TokenStream::SpaceOrLineFeed(tokens) => {
+                self.held.extend(tokens);
+                Ok(())
+            },
+        }
+    }
+
+    pub fn chunk(&mut self, path: &Path, output: &mut dyn Write) -> Result<(), MergeErr> {
+        let preamble: &[u8] = if self.chunks > 0 { &self.preamble } else { &[] };
+        let mut parser = open_chunk(path, preamble, self.dialect)?;
+        self.chunks += 1;
+        self.chunk_start = true;
+
+        while let Some(item) = parser.token_stream().map_err(MergeErr::Syntax)? {
+            self.item(item, output)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(&mut self, output: &mut dyn Write) -> Result<(), MergeErr> {
+        self.flush(output)?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+pub(crate) mod merge_test{
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::dialect::Dialect;
+    use crate::splitter::{FileState, Splitter, SplitterSettings, SplitterState};
+    use crate::splitter::splitter_test::test_settings;
+
+    use super::Merger;
+
+    // splits `path` and writes the chunks to the temp dir. returns them
+    // with the preamble repeated at their top. like the manifest in main
+    // only chunks that got their stats when they were closed are listed.
+    pub(crate) fn split(
+        path: &str,
+        write: usize,
+        replay_preamble: bool,
+        rows_per_insert: Option<usize>,
+        prefix: &str,
+    ) -> (Vec<PathBuf>, Vec<u8>) {
+        split_with(SplitterSettings { write, replay_preamble, rows_per_insert, ..test_settings(path) }, prefix)
+    }
+
+    pub(crate) fn split_with(settings: SplitterSettings, prefix: &str) -> (Vec<PathBuf>, Vec<u8>) {
+        let path = settings.path.clone();
+        let mut splitter = Splitter::new(settings);
+        let mut chunks: Vec<Vec<u8>> = vec![];
+        let mut recorded = vec![];
+        loop {
+            match splitter.process() {
+                SplitterState::Chunk(file_state, tokens) => {
+                    if file_state == FileState::New || chunks.is_empty() {
+                        if !chunks.is_empty() {
+                            recorded.push(splitter.completed_chunk().is_some());
+                        }
+                        chunks.push(vec![]);
+                    }
+                    chunks.last_mut().unwrap().extend(tokens);
+                },
+                SplitterState::SyntaxErr(e) |
+                SplitterState::Skipped(e, ..) => panic!("{}", e.text),
+                SplitterState::Done => break,
+            }
+        }
+        recorded.push(splitter.completed_chunk().is_some());
+
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let chunks = chunks.iter().zip(recorded).filter(|(_, recorded)| *recorded).map(|(chunk, _)| chunk);
+        let files = chunks.enumerate().map(|(index, chunk)| {
+            let file = env::temp_dir().join(format!("sql-split-{}-{}-{}.sql", prefix, name, index + 1));
+            fs::write(&file, chunk).unwrap();
+            file
+        }).collect();
+        (files, splitter.replayed_preamble().to_vec())
+    }
+
+    fn merge(chunks: &[PathBuf], coalesce: bool) -> String {
+        merge_as(chunks, coalesce, Dialect::Mysql)
+    }
+
+    fn merge_as(chunks: &[PathBuf], coalesce: bool, dialect: Dialect) -> String {
+        let mut merger = Merger::new(coalesce, dialect);
+        let mut output = vec![];
+        for chunk in chunks {
+            if merger.chunk(chunk, &mut output).is_err() {
+                panic!("merge failed");
+            }
+        }
+        if merger.finish(&mut output).is_err() {
+            panic!("merge failed");
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn restores_split(){
+        for (path, write, replay_preamble) in [
+            ("../resources/test_db/big.sql", 50 * 1024, false),
+            ("../resources/test_db/preamble.sql", 100, true),
+            ("../resources/test_db/preamble.sql", 1, true),
+            ("../resources/test_db/mysqldump.sql", 200, true),
+            // chunks start with INSERTs of their own as well as cut ones
+            ("../resources/test_db/single-row.sql", 1, false),
+            ("../resources/test_db/single-row.sql", 40, false),
+        ] {
+            let (chunks, _) = split(path, write, replay_preamble, None, "merge");
+            assert!(chunks.len() > 2, "{}", path);
+            assert_eq!(merge(&chunks, false), fs::read_to_string(path).unwrap(), "{}", path);
+            for chunk in chunks {
+                fs::remove_file(chunk).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn chunk_transactions(){
+        let path = "../resources/test_db/sqlite.sql";
+        let (chunks, _) = split_with(SplitterSettings {
+            write: 200,
+            replay_preamble: true,
+            dialect: Dialect::Sqlite,
+            chunk_transactions: true,
+            ..test_settings(path)
+        }, "merge-transactions");
+        assert!(chunks.len() > 3);
+
+        // only the COMMIT and BEGIN of the dump are left
+        assert_eq!(merge_as(&chunks, false, Dialect::Sqlite), fs::read_to_string(path).unwrap());
+        for chunk in chunks {
+            fs::remove_file(chunk).unwrap();
+        }
+    }
+
+    #[test]
+    fn coalesce(){
+        let chunks = [PathBuf::from("../resources/test_db/small.sql")];
+        let merged = merge(&chunks, true);
+        assert_eq!(merged.matches("INSERT INTO `hello`").count(), 1);
+        assert!(merged.contains("(1, 34, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1),\n(2, 35, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1);"));
+
+        // without --coalesce a single file comes out as is
+        assert_eq!(merge(&chunks, false), fs::read_to_string(&chunks[0]).unwrap());
+    }
+}
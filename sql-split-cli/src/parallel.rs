@@ -0,0 +1,434 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use sql_split_reader::Reader;
+
+use crate::dialect::Dialect;
+use crate::parser::parser::{Parser, StreamSource, TokenStream};
+use crate::tokenizer::{
+    tokenizer::Tokenizer,
+    token_err::TokenErr,
+};
+
+pub const SEGMENT_SIZE: usize = 64 * 1024 * 1024;
+
+// a token stream together with the input range it was parsed from. the
+// delimiters are None while it is `;`.
+struct Item {
+    start: usize,
+    end: usize,
+    stream: TokenStream,
+    // delimiter the item was parsed with and the one after it
+    delimiter: Option<Vec<u8>>,
+    next_delimiter: Option<Vec<u8>>,
+}
+
+fn custom_delimiter(parser: &Parser) -> Option<Vec<u8>> {
+    Some(parser.delimiter().to_vec()).filter(|delimiter| delimiter != b";")
+}
+
+// splits a seekable file into byte segments that are parsed by worker
+// threads. every worker resyncs on a line that looks like a statement
+// boundary and parses until it runs past the start of the next segment.
+//
+// the segments are stitched back together in order. an item is only
+// accepted when it starts exactly where the previous one ended and was
+// parsed with the same DELIMITER, so a boundary guessed wrong (say a line
+// inside a string or a procedure after DELIMITER ;;) never makes it into
+// the output. gaps and mismatches are parsed sequentially in this thread,
+// which keeps the result identical to a plain Parser.
+pub struct ParallelParser {
+    path: PathBuf,
+    fast_scan: bool,
+    dialect: Dialect,
+    len: usize,
+    segments: usize,
+    threads: usize,
+    // next segment to hand to the workers
+    dispatched: usize,
+    // next segment to consume
+    current: usize,
+    jobs: Option<Sender<usize>>,
+    results: Receiver<(usize, Vec<Item>)>,
+    completed: BTreeMap<usize, Vec<Item>>,
+    pending: VecDeque<Item>,
+    peeked: Option<Item>,
+    // offset where the next item has to start
+    expect: usize,
+    // DELIMITER in use at `expect`
+    delimiter: Option<Vec<u8>>,
+    fallback: Option<Parser>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+fn open_parser(path: &Path, offset: usize, fast_scan: bool, dialect: Dialect) -> Parser {
+    let file = File::open(path).expect("unable to open input");
+    let mut reader = Reader::new(file);
+    if offset > 0 {
+        reader.seek(offset);
+    }
+
+    let mut tokenizer = Tokenizer::new(reader);
+    tokenizer.set_fast_scan(fast_scan);
+    tokenizer.set_dialect(dialect);
+    Parser::new(tokenizer)
+}
+
+fn starts_with_insert(head: &[u8]) -> bool {
+    head.len() >= 11 && head[..11].eq_ignore_ascii_case(b"insert into")
+}
+
+// true when the bytes right before `offset` close a values tuple: `),\n`
+fn after_tuple(file: &mut File, offset: usize) -> bool {
+    if offset < 3 {
+        return false
+    }
+
+    let from = offset.saturating_sub(4);
+    let mut tail = vec![0; offset - from];
+    if file.seek(SeekFrom::Start(from as u64)).is_err() || file.read_exact(&mut tail).is_err() {
+        return false
+    }
+
+    tail.ends_with(b"),\n") || tail.ends_with(b"),\r\n")
+}
+
+// finds the first line in [from, until) that starts with `INSERT INTO`
+// or with `(` right after a `),` line.
+fn find_boundary(path: &Path, from: usize, until: usize) -> Option<usize> {
+    let mut reader = Reader::new(File::open(path).ok()?);
+    let mut file = File::open(path).ok()?;
+    reader.seek(from);
+
+    loop {
+        // skip the rest of the current line
+        reader.skip_until_any(b"\n")?;
+        reader.increment_index();
+
+        let start = reader.position();
+        if start >= until {
+            return None
+        }
+
+        let mut head = vec![];
+        while head.len() < 11 {
+            match reader.peek() {
+                Some(b'\n') | None => break,
+                Some(byte) => {
+                    head.push(byte);
+                    reader.increment_index();
+                },
+            }
+        }
+
+        if starts_with_insert(&head) {
+            return Some(start)
+        }
+
+        if head.first() == Some(&b'(') && after_tuple(&mut file, start) {
+            return Some(start)
+        }
+    }
+}
+
+fn parse_segment(path: &Path, fast_scan: bool, dialect: Dialect, from: usize, until: usize, first: bool) -> Vec<Item> {
+    let start = if first {
+        Some(0)
+    }else{
+        find_boundary(path, from, until)
+    };
+
+    let mut items = vec![];
+    let start = match start {
+        Some(start) => start,
+        None => return items,
+    };
+
+    let mut parser = open_parser(path, start, fast_scan, dialect);
+    let mut position = start;
+    while position < until {
+        let delimiter = custom_delimiter(&parser);
+        match parser.token_stream() {
+            Ok(Some(stream)) => {
+                let end = StreamSource::position(&parser);
+                let next_delimiter = custom_delimiter(&parser);
+                items.push(Item { start: position, end, stream, delimiter, next_delimiter });
+                position = end;
+            },
+            // errors are left to the sequential fallback. if the boundary
+            // was real it reports the same error at the same place.
+            Ok(None) | Err(_) => break,
+        }
+    }
+
+    items
+}
+
+impl ParallelParser {
+    // parses from `start` to the end of the file
+    pub fn new(path: &Path, threads: usize, segment_size: usize, fast_scan: bool, dialect: Dialect, start: usize) -> Self {
+        let len = std::fs::metadata(path).map(|meta| meta.len() as usize).unwrap_or(0);
+        let segment_size = segment_size.max(1);
+        let segments = len.saturating_sub(start).div_ceil(segment_size);
+        let threads = threads.max(1);
+
+        let (jobs, job_receiver) = channel::<usize>();
+        let (result_sender, results) = channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let workers = (0..threads).map(|_| {
+            let jobs = Arc::clone(&job_receiver);
+            let results = result_sender.clone();
+            let path = path.to_path_buf();
+            thread::spawn(move || loop {
+                let index = match jobs.lock().unwrap().recv() {
+                    Ok(index) => index,
+                    Err(_) => break,
+                };
+
+                let from = start + index * segment_size;
+                let until = (from + segment_size).min(len);
+                let items = parse_segment(&path, fast_scan, dialect, from, until, index == 0);
+                if results.send((index, items)).is_err() {
+                    break;
+                }
+            })
+        }).collect();
+
+        let mut parser = Self {
+            path: path.to_path_buf(),
+            fast_scan,
+            dialect,
+            len,
+            segments,
+            threads,
+            dispatched: 0,
+            current: 0,
+            jobs: Some(jobs),
+            results,
+            completed: BTreeMap::new(),
+            pending: VecDeque::new(),
+            peeked: None,
+            expect: start,
+            delimiter: None,
+            fallback: None,
+            workers,
+        };
+        parser.dispatch();
+        parser
+    }
+
+    // keeps a bounded number of segments in flight so memory stays flat
+    fn dispatch(&mut self) {
+        let limit = (self.current + self.threads * 2).min(self.segments);
+        while self.dispatched < limit {
+            if let Some(jobs) = &self.jobs {
+                let _ = jobs.send(self.dispatched);
+            }
+            self.dispatched += 1;
+        }
+
+        if self.dispatched == self.segments {
+            // closing the queue lets idle workers exit
+            self.jobs = None;
+        }
+    }
+
+    fn next_item(&mut self) -> Option<Item> {
+        if let Some(item) = self.peeked.take() {
+            return Some(item)
+        }
+
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item)
+            }
+
+            if self.current >= self.segments {
+                return None
+            }
+
+            while !self.completed.contains_key(&self.current) {
+                let (index, items) = self.results.recv().expect("parallel worker died");
+                self.completed.insert(index, items);
+            }
+
+            let items = self.completed.remove(&self.current).unwrap();
+            self.pending = items.into();
+            self.current += 1;
+            self.dispatch();
+        }
+    }
+
+    fn fallback_stream(&mut self) -> Result<Option<TokenStream>, TokenErr> {
+        if self.fallback.is_none() {
+            let mut parser = open_parser(&self.path, self.expect, self.fast_scan, self.dialect);
+            if let Some(delimiter) = &self.delimiter {
+                parser.set_delimiter(delimiter);
+            }
+            self.fallback = Some(parser);
+        }
+
+        let parser = self.fallback.as_mut().unwrap();
+        let stream = parser.token_stream()?;
+        self.expect = StreamSource::position(parser);
+        self.delimiter = custom_delimiter(parser);
+        Ok(stream)
+    }
+}
+
+impl StreamSource for ParallelParser {
+    fn token_stream(&mut self) -> Result<Option<TokenStream>, TokenErr> {
+        loop {
+            match self.next_item() {
+                // already covered by the previous segment
+                Some(item) if item.start < self.expect => continue,
+                Some(item) if item.start == self.expect && item.delimiter == self.delimiter => {
+                    self.fallback = None;
+                    self.expect = item.end;
+                    self.delimiter = item.next_delimiter;
+                    return Ok(Some(item.stream))
+                },
+                // parsed without the DELIMITER of the input at this point
+                Some(item) if item.start == self.expect => return self.fallback_stream(),
+                Some(item) => {
+                    self.peeked = Some(item);
+                    return self.fallback_stream()
+                },
+                None => {
+                    if self.expect >= self.len && self.fallback.is_none() {
+                        return Ok(None)
+                    }
+                    return self.fallback_stream()
+                },
+            }
+        }
+    }
+
+    fn position(&self) -> usize {
+        self.expect
+    }
+
+    // errors only come from the fallback parser
+    fn recover(&mut self) -> (usize, usize) {
+        match self.fallback.as_mut() {
+            Some(parser) => {
+                let skipped = parser.recover();
+                self.expect = skipped.1;
+                skipped
+            },
+            None => (self.expect, self.expect),
+        }
+    }
+}
+
+impl Drop for ParallelParser {
+    fn drop(&mut self) {
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod parallel_test{
+    use std::fs::File;
+    use std::path::Path;
+    use sql_split_reader::Reader;
+    use crate::dialect::Dialect;
+    use crate::parser::parser::{Parser, StreamSource, TokenStream};
+    use crate::tokenizer::tokenizer::Tokenizer;
+
+    use super::ParallelParser;
+
+    fn collect(source: &mut dyn StreamSource) -> Vec<TokenStream> {
+        let mut stream = vec![];
+        while let Some(item) = source.token_stream().unwrap() {
+            stream.push(item);
+        }
+        stream
+    }
+
+    fn sequential(path: &str, dialect: Dialect) -> Vec<TokenStream> {
+        let mut tokenizer = Tokenizer::new(Reader::new(File::open(path).unwrap()));
+        tokenizer.set_dialect(dialect);
+        collect(&mut Parser::new(tokenizer))
+    }
+
+    #[test]
+    fn matches_sequential_parser(){
+        for path in ["../resources/test_db/small.sql", "../resources/test_db/big.sql"] {
+            let expected = sequential(path, Dialect::Mysql);
+            for segment_size in [97, 4096, 100_000] {
+                let mut parser = ParallelParser::new(Path::new(path), 4, segment_size, true, Dialect::Mysql, 0);
+                assert_eq!(collect(&mut parser), expected, "{} / {}", path, segment_size);
+            }
+        }
+    }
+
+    #[test]
+    fn carries_delimiter(){
+        // segments start on INSERT lines inside the DELIMITER ;; bodies,
+        // and in the GO batches of SQL Server
+        for (path, dialect) in [
+            ("../resources/test_db/routines.sql", Dialect::Mysql),
+            ("../resources/test_db/delimiter.sql", Dialect::Mysql),
+            ("../resources/test_db/mssql.sql", Dialect::Mssql),
+        ] {
+            let expected = sequential(path, dialect);
+            for segment_size in 1..200 {
+                let mut parser = ParallelParser::new(Path::new(path), 3, segment_size, true, dialect, 0);
+                assert_eq!(collect(&mut parser), expected, "{} / {}", path, segment_size);
+            }
+        }
+    }
+
+    #[test]
+    fn other_dialects(){
+        // COPY data of postgres can hold lines that look like INSERTs and
+        // values tuples, the segments that resync on them must be dropped
+        for (path, dialect) in [
+            ("../resources/test_db/postgres.sql", Dialect::Postgres),
+            ("../resources/test_db/copy.sql", Dialect::Postgres),
+            ("../resources/test_db/copy-inserts.sql", Dialect::Postgres),
+            ("../resources/test_db/sqlite.sql", Dialect::Sqlite),
+        ] {
+            let expected = sequential(path, dialect);
+            for segment_size in 1..300 {
+                let mut parser = ParallelParser::new(Path::new(path), 3, segment_size, true, dialect, 0);
+                assert_eq!(collect(&mut parser), expected, "{} / {}", path, segment_size);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_file(){
+        let mut parser = ParallelParser::new(Path::new("../resources/test_db/empty.sql"), 2, 10, true, Dialect::Mysql, 0);
+        assert!(collect(&mut parser).is_empty());
+    }
+
+    #[test]
+    fn starts_at_offset(){
+        let path = "../resources/test_db/big.sql";
+        let mut parser = ParallelParser::new(Path::new(path), 3, 4096, true, Dialect::Mysql, 0);
+        let mut expected = vec![];
+        let mut offset = 0;
+        while let Some(item) = parser.token_stream().unwrap() {
+            if offset == 0 && parser.position() > 20_000 {
+                offset = parser.position();
+            }else if offset > 0 {
+                expected.push(item);
+            }
+        }
+
+        let mut parser = ParallelParser::new(Path::new(path), 3, 4096, true, Dialect::Mysql, offset);
+        assert_eq!(collect(&mut parser), expected);
+    }
+}
@@ -1 +1,5 @@
+#[allow(clippy::module_inception)]
 pub mod parser;
+pub mod statement;
+pub mod table;
+pub mod values;
@@ -1,3 +1,7 @@
+use std::fs::File;
+use sql_split_reader::Reader;
+use crate::dialect::Dialect;
+use crate::parser::statement::{batch_separator, copy_from_stdin, creates_routine, StatementKind};
 use crate::tokenizer::{
     token_err::TokenErr,
     token::Token,
@@ -8,83 +12,339 @@ use crate::tokenizer::{
 pub enum TokenStream {
     Insert(Vec<u8>, Vec<u8>),
     ValuesTuple(Vec<u8>),
-    Block(Vec<u8>),
+    Block(Vec<u8>, StatementKind),
     Comment(Vec<u8>),
     SpaceOrLineFeed(Vec<u8>),
 }
 
+impl TokenStream {
+    // a Block with the kind of statement it holds
+    pub fn block(tokens: Vec<u8>) -> Self {
+        let kind = StatementKind::of(&tokens);
+        TokenStream::Block(tokens, kind)
+    }
+}
+
+// hands out token streams in input order. implemented by the sequential
+// Parser and by parallel::ParallelParser.
+pub trait StreamSource {
+    fn token_stream(&mut self) -> Result<Option<TokenStream>, TokenErr>;
+    // input offset right after the last token stream
+    fn position(&self) -> usize;
+    // skips the statement that failed to parse. returns the input range
+    // that was skipped.
+    fn recover(&mut self) -> (usize, usize);
+}
+
 pub struct Parser {
     tokenizer: Tokenizer,
+    // input offset where the current statement started
+    start: usize,
+    // what ends a statement, changed by the DELIMITER command of MySQL
+    delimiter: Vec<u8>,
+}
+
+impl StreamSource for Parser {
+    fn token_stream(&mut self) -> Result<Option<TokenStream>, TokenErr> {
+        Parser::token_stream(self)
+    }
+
+    fn position(&self) -> usize {
+        self.tokenizer.position()
+    }
+
+    fn recover(&mut self) -> (usize, usize) {
+        Parser::recover(self)
+    }
 }
 
+// no server has this version. MariaDB puts client commands in such
+// comments, e.g. /*M!999999\- enable the sandbox mode */
+const NEVER_RUN: u32 = 999999;
+
+// keywords that start a new statement, an INSERT can not run into them
+const STATEMENTS: [&str; 8] = ["insert", "create", "drop", "alter", "lock", "unlock", "pragma", "commit"];
+
+// in a SQL Server batch a statement may go without `;`, it ends where a
+// line starts with one of these or with STATEMENTS
+const BATCH_STATEMENTS: [&str; 14] = [
+    "set", "use", "exec", "execute", "print", "declare", "begin", "if",
+    "select", "update", "delete", "truncate", "grant", "revoke",
+];
+
 impl Parser {
     pub fn new(tokenizer: Tokenizer) -> Self {
-        Self { tokenizer }
+        let start = tokenizer.position();
+        Self { tokenizer, start, delimiter: vec![b';'] }
+    }
+
+    pub fn from_file(file: File, fast_scan: bool, dialect: Dialect) -> Self {
+        let mut tokenizer = Tokenizer::new(Reader::new(file));
+        tokenizer.set_fast_scan(fast_scan);
+        tokenizer.set_dialect(dialect);
+        Self::new(tokenizer)
+    }
+
+    // what ends the statements, `;` unless a DELIMITER command changed it
+    pub fn delimiter(&self) -> &[u8] {
+        &self.delimiter
+    }
+
+    // a parser started in the middle of the input continues after a
+    // DELIMITER command it did not read
+    pub fn set_delimiter(&mut self, delimiter: &[u8]) {
+        self.delimiter = delimiter.to_vec();
+    }
+
+    fn err(&self, text: &'static str) -> TokenErr {
+        TokenErr{
+            text,
+            pos: self.start,
+        }
+    }
+
+    // skips the rest of a broken statement so parsing can carry on
+    // with the next one
+    pub fn recover(&mut self) -> (usize, usize) {
+        self.tokenizer.skip_to_statement(self.start);
+        (self.start, self.tokenizer.position())
     }
     
-    pub fn read_while(&mut self, token: &Token) -> Result<Vec<u8>, TokenErr> {
-        let mut collection = vec![];
+    // the line that follows starts a new statement or is a GO line. only
+    // SQL Server scripts end statements this way.
+    fn line_ends_statement(&mut self) -> bool {
+        if !self.tokenizer.syntax().batches {
+            return false
+        }
+
+        match self.tokenizer.next_line() {
+            Some(line) => {
+                let word = line.iter()
+                    .take_while(|byte| byte.is_ascii_alphabetic())
+                    .map(|byte| byte.to_ascii_lowercase() as char)
+                    .collect::<String>();
+                batch_separator(&line) || STATEMENTS.iter().chain(BATCH_STATEMENTS.iter()).any(|keyword| *keyword == word)
+            },
+            None => false,
+        }
+    }
+
+    // reads the rest of a statement up to and including its `;` or the
+    // delimiter set with DELIMITER. `collection` holds its first token.
+    fn statement(&mut self, mut collection: Vec<u8>) -> Result<Vec<u8>, TokenErr> {
+        if self.delimiter != b";" {
+            collection.extend(self.read_until_delimiter()?);
+            return Ok(collection)
+        }
+        if !self.tokenizer.syntax().batches {
+            return self.compound_statement(collection)
+        }
+
+        while !self.line_ends_statement() {
+            match self.tokenizer.token()? {
+                Some(token @ Token::SemiColon) => {
+                    collection.extend(token.value());
+                    break
+                },
+                Some(token) => collection.extend(token.value()),
+                // the last statement of a script
+                None => break,
+            }
+        }
+        Ok(collection)
+    }
+
+    // the `;` inside the BEGIN ... END of CREATE TRIGGER, PROCEDURE or
+    // FUNCTION do not end it, as SQLite and Oracle write them without
+    // DELIMITER. CASE ... END nests in there too.
+    fn compound_statement(&mut self, mut collection: Vec<u8>) -> Result<Vec<u8>, TokenErr> {
+        let mut depth = 0;
+        // END IF, END LOOP, END WHILE and END REPEAT close no BEGIN
+        let mut after_end = false;
         loop {
+            let token = match self.tokenizer.token()? {
+                Some(token) => token,
+                None => return Err(self.err("Unexpected end of the file.")),
+            };
+
+            match token {
+                Token::SemiColon if depth == 0 => {
+                    collection.extend(token.value());
+                    return Ok(collection)
+                },
+                Token::Space |
+                Token::LineFeed(_) |
+                Token::Comment(_) |
+                Token::InlineComment(_) => {},
+                // part of a name like v_end or begin_date
+                Token::Keyword(_) if collection.last().is_some_and(|byte| byte.is_ascii_alphanumeric() || *byte == b'_') ||
+                    self.tokenizer.word_continues() => after_end = false,
+                Token::Keyword(_) if after_end && ["if", "loop", "while", "repeat"].iter().any(|keyword| token.keyword(keyword)) => {
+                    depth += 1;
+                    after_end = false;
+                },
+                Token::Keyword(_) if after_end && token.keyword("case") => after_end = false,
+                Token::Keyword(_) if token.keyword("begin") || token.keyword("case") => {
+                    if depth > 0 || creates_routine(&collection) {
+                        depth += 1;
+                    }
+                },
+                Token::Keyword(_) if token.keyword("end") && depth > 0 => {
+                    depth -= 1;
+                    after_end = true;
+                },
+                _ => after_end = false,
+            }
+            collection.extend(token.value());
+        }
+    }
+
+    fn read_until_delimiter(&mut self) -> Result<Vec<u8>, TokenErr> {
+        let mut collection = vec![];
+        while !collection.ends_with(&self.delimiter) {
             match self.tokenizer.token()? {
-                Some(t) => {
-                    if t == *token {
-                        collection.extend(t.value());
-                        break
-                    }else{
-                        collection.extend(t.value());
+                Some(token) => collection.extend(token.value()),
+                None => return Err(self.err("Unexpected end of the file.")),
+            }
+        }
+        Ok(collection)
+    }
+
+    // DELIMITER ;; sets what ends the statements that follow, up to the
+    // end of its line
+    fn delimiter_command(&mut self, token: Token) -> TokenStream {
+        let rest = self.tokenizer.read_line();
+        let delimiter = rest.trim_ascii();
+        if !delimiter.is_empty() {
+            self.delimiter = delimiter.to_vec();
+        }
+
+        let mut output = token.value();
+        output.extend(rest);
+        TokenStream::block(output)
+    }
+
+    // /*!40101 SET NAMES utf8 */; is a statement to MySQL. it takes what
+    // follows on the same line up to the `;`, e.g. the other comments of
+    // /*!50003 CREATE*/ /*!50017 DEFINER=`root`@`%`*/ /*!50003 TRIGGER ...*/;;
+    fn conditional_statement(&mut self, token: Token) -> Result<TokenStream, TokenErr> {
+        let mut output = token.value();
+        while !output.ends_with(&self.delimiter) && self.tokenizer.next_line().is_none() {
+            match self.tokenizer.token()? {
+                Some(token) => output.extend(token.value()),
+                None => break,
+            }
+        }
+        Ok(TokenStream::block(output))
+    }
+
+    // GO line of a SQL Server script, without its line feed
+    fn batch_separator(&mut self) -> Option<Vec<u8>> {
+        if !self.tokenizer.syntax().batches {
+            return None
+        }
+
+        let line = self.tokenizer.peek_line();
+        if batch_separator(&line) {
+            Some(self.tokenizer.take(line.len()))
+        }else{
+            None
+        }
+    }
+
+    // reads the rest of a values tuple after the opening `(`.
+    fn tuple_body(&mut self) -> Result<Vec<u8>, TokenErr> {
+        if self.tokenizer.fast_scan() {
+            return self.tokenizer.tuple_body()
+        }
+
+        let start = self.tokenizer.position().saturating_sub(1);
+        let mut collection = vec![];
+        let mut depth = 1;
+        while depth > 0 {
+            match self.tokenizer.token()? {
+                Some(Token::SemiColon) => {
+                    return Err(TokenErr{
+                        text: "Unbalanced parenthesis in values tuple.",
+                        pos: start,
+                    })
+                },
+                Some(token) => {
+                    match token {
+                        Token::LP => depth += 1,
+                        Token::RP => depth -= 1,
+                        _ => {},
                     }
+                    collection.extend(token.value());
                 },
                 None => {
                     return Err(TokenErr{
-                        text:"invalid end of file"
+                        text: "Unclosed values tuple.",
+                        pos: start,
                     })
-                }
+                },
             }
         }
 
         Ok(collection)
     }
 
-    pub fn values(&mut self) -> Result<Vec<u8>, TokenErr> {
-        let mut collection = vec![];
+    // reads what follows the `)` of a values tuple up to the `,` or `;`
+    // that ends it, e.g. ON DUPLICATE KEY UPDATE `a` = VALUES(`a`)
+    fn tuple_end(&mut self, collection: &mut Vec<u8>) -> Result<(), TokenErr> {
+        let mut depth = 0;
         loop {
+            if depth == 0 && self.line_ends_statement() {
+                return Ok(())
+            }
+
+            let pos = self.tokenizer.position();
             match self.tokenizer.token()? {
-                Some(token @ Token::LP) => {
+                Some(token @ (Token::Comma | Token::SemiColon)) if depth == 0 => {
                     collection.extend(token.value());
-                    match self.read_while(&Token::RP) {
-                        Ok(val) => {
-                            collection.extend(val);
-                        },
-                        Err(e) => return Err(e),
-                    }                    
+                    return Ok(())
                 },
-                Some(token @ Token::Comma) => {
-                    collection.extend(token.value());
-                    break;
+                Some(Token::RP) if depth == 0 => {
+                    return Err(TokenErr{
+                        text: "Unbalanced parenthesis in values tuple.",
+                        pos,
+                    })
                 },
-                Some(token @ Token::SemiColon) => {
-                    collection.extend(token.value());
-                    break;
+                Some(token) if depth == 0 && STATEMENTS.iter().any(|keyword| token.keyword(keyword)) => {
+                    return Err(TokenErr{
+                        text: "INSERT without terminator.",
+                        pos,
+                    })
                 },
                 Some(token) => {
+                    match token {
+                        Token::LP => depth += 1,
+                        Token::RP => depth -= 1,
+                        _ => {},
+                    }
                     collection.extend(token.value());
                 },
+                None if self.tokenizer.syntax().batches => return Ok(()),
                 None => {
                     return Err(TokenErr{
-                        text: "Unable to parse values."
+                        text: "INSERT without terminator.",
+                        pos,
                     })
-                }
+                },
             }
         }
-        Ok(collection)
     }
 
-    pub fn values_tuple(&mut self) -> Result<Vec<u8>, TokenErr> {
+    pub fn values(&mut self) -> Result<Vec<u8>, TokenErr> {
         let mut collection = vec![];
-        let value = self.read_while(&Token::RP)?; 
-        collection.extend(value);
         loop {
             match self.tokenizer.token()? {
+                Some(token @ Token::LP) => {
+                    collection.extend(token.value());
+                    collection.extend(self.tuple_body()?);
+                    self.tuple_end(&mut collection)?;
+                    break;
+                },
                 Some(token @ Token::Comma) => {
                     collection.extend(token.value());
                     break;
@@ -93,15 +353,20 @@ impl Parser {
                     collection.extend(token.value());
                     break;
                 },
-                Some(token) => collection.extend(token.value()),
-                None => {
-                    return Err(TokenErr{
-                        text: "Unable to parse values."
-                    })
+                Some(token) => {
+                    collection.extend(token.value());
                 },
+                None => {
+                    return Err(self.err("Unable to parse values."))
+                }
             }
         }
+        Ok(collection)
+    }
 
+    pub fn values_tuple(&mut self) -> Result<Vec<u8>, TokenErr> {
+        let mut collection = self.tuple_body()?;
+        self.tuple_end(&mut collection)?;
         Ok(collection)
     }
 
@@ -124,9 +389,7 @@ impl Parser {
                     }
                 },
                 None => {
-                    return Err(TokenErr{
-                        text: "Incomplete Insert statement."
-                    })
+                    return Err(self.err("Incomplete Insert statement."))
                 },
             }
         }
@@ -135,6 +398,11 @@ impl Parser {
     }
 
     pub fn token_stream(&mut self) -> Result<Option<TokenStream>, TokenErr> {
+        self.start = self.tokenizer.position();
+        if let Some(go) = self.batch_separator() {
+            return Ok(Some(TokenStream::block(go)))
+        }
+
         match self.tokenizer.token()? {
             Some(token) => {
                 match token {
@@ -147,15 +415,19 @@ impl Parser {
 
                             let (insert, insert_stmt) = self.insert(token.value())?;
                             Ok(Some(TokenStream::Insert(insert, insert_stmt)))
+                        }else if token.keyword("delimiter") && self.tokenizer.syntax().delimiter_command {
+                            Ok(Some(self.delimiter_command(token)))
                         }else{
                             // we assume its a block handle blocks
                             // anything that ends with `;` and 
                             // start with create, drop or set etc etc
-                            match self.read_while(&Token::SemiColon) {
-                                Ok(val) => {
-                                    let mut output = token.value();
-                                    output.extend(val);
-                                    Ok(Some(TokenStream::Block(output)))
+                            match self.statement(token.value()) {
+                                Ok(mut output) => {
+                                    // pg_dump writes the rows of a table as COPY data
+                                    if self.tokenizer.syntax().copy_data && copy_from_stdin(&output) {
+                                        output.extend(self.tokenizer.copy_data()?);
+                                    }
+                                    Ok(Some(TokenStream::block(output)))
                                 },
                                 Err(e) => Err(e)  
                             }
@@ -166,19 +438,27 @@ impl Parser {
                         output.extend(self.values_tuple()?);
                         Ok(Some(TokenStream::ValuesTuple(output)))
                     }
+                    Token::ConditionalComment(version, _) if version.is_none_or(|version| version < NEVER_RUN) => {
+                        Ok(Some(self.conditional_statement(token)?))
+                    },
                     Token::Comment(_) | 
+                    Token::ConditionalComment(..) |
                     Token::InlineComment(_) => {
                         Ok(Some(TokenStream::Comment(token.value())))
                     },
+                    // \connect db
+                    Token::Ignore(b'\\') if self.tokenizer.syntax().backslash_commands => {
+                        let mut output = token.value();
+                        output.extend(self.tokenizer.read_line());
+                        Ok(Some(TokenStream::block(output)))
+                    },
                     Token::RP |
                     Token::Dot |
                     Token::String(_) |
                     Token::Identifier(_) |
                     Token::Comma |
                     Token::Ignore(_) => {
-                        Err(TokenErr{
-                            text: "Invalid sql file."
-                        })
+                        Err(self.err("Invalid sql file."))
                     },
                     Token::SemiColon |
                     Token::Space |
@@ -200,6 +480,8 @@ impl Parser {
 mod reader_test{
     use std::fs::File;
     use sql_split_reader::Reader;
+    use crate::dialect::Dialect;
+    use crate::parser::statement::StatementKind;
     use crate::tokenizer::tokenizer::Tokenizer;
     use crate::tokenizer::token_err::TokenErr;
 
@@ -208,21 +490,11 @@ mod reader_test{
 
     type TS = Result<Option<TokenStream>, TokenErr>;    
     fn is_space(value: TS) -> bool {
-        match value {
-            Ok(Some(TokenStream::SpaceOrLineFeed(_))) => {
-                true
-            },
-            _ => false,
-        }
+        matches!(value, Ok(Some(TokenStream::SpaceOrLineFeed(_))))
     }
 
     fn is_comment(value: TS) -> bool {
-        match value {
-            Ok(Some(TokenStream::Comment(_))) => {
-                true
-            },
-            _ => false,
-        }
+        matches!(value, Ok(Some(TokenStream::Comment(_))))
     }
 
     fn valid_values_tuple(value: TS) -> (bool, &'static str) {
@@ -239,7 +511,7 @@ mod reader_test{
 
     fn valid_block(value: TS) -> (bool, &'static str) {
         match value {
-            Ok(Some(TokenStream::Block(tokens))) => {
+            Ok(Some(TokenStream::Block(tokens, _))) => {
                 match tokens[tokens.len() - 1] {
                     b';' => (true, ""),
                     _ => (false, "Last token should be semicolon"),
@@ -333,5 +605,190 @@ mod reader_test{
         assert!(state, "{}", msg);
     }
 
+    fn collect_stream(path: &str, fast_scan: bool, dialect: Dialect) -> Vec<TokenStream> {
+        let file = File::open(path).unwrap();
+        let mut tokenizer = Tokenizer::new(Reader::new(file));
+        tokenizer.set_fast_scan(fast_scan);
+        tokenizer.set_dialect(dialect);
+        let mut parser = Parser::new(tokenizer);
+        let mut stream = vec![];
+        while let Some(item) = parser.token_stream().unwrap() {
+            stream.push(item);
+        }
+        stream
+    }
+
+    #[test]
+    fn fast_scan_matches_byte_scan(){
+        // /* */ comments are scanned too, nested ones in postgres and mssql
+        for (path, dialect) in [
+            ("../resources/test_db/small.sql", Dialect::Mysql),
+            ("../resources/test_db/big.sql", Dialect::Mysql),
+            ("../resources/test_db/mariadb.sql", Dialect::Mysql),
+            ("../resources/test_db/create-table-with-comments.sql", Dialect::Mysql),
+            ("../resources/test_db/nested-comments.sql", Dialect::Postgres),
+            ("../resources/test_db/mssql.sql", Dialect::Mssql),
+        ] {
+            let fast = collect_stream(path, true, dialect);
+            let slow = collect_stream(path, false, dialect);
+            assert!(!fast.is_empty());
+            assert_eq!(fast, slow, "{}", path);
+        }
+    }
+
+    fn blocks(stream: &[TokenStream]) -> Vec<String> {
+        stream.iter().filter_map(|item| match item {
+            TokenStream::Block(tokens, _) => Some(String::from_utf8_lossy(tokens).into_owned()),
+            _ => None,
+        }).collect()
+    }
+
+    fn concat(stream: Vec<TokenStream>) -> Vec<u8> {
+        stream.into_iter().flat_map(|item| match item {
+            TokenStream::Insert(tokens, _) |
+            TokenStream::ValuesTuple(tokens) |
+            TokenStream::Block(tokens, _) |
+            TokenStream::Comment(tokens) |
+            TokenStream::SpaceOrLineFeed(tokens) => tokens,
+        }).collect()
+    }
+
+    #[test]
+    fn dialects(){
+        for fast_scan in [true, false] {
+            // dollar quoted function body, COPY data and psql meta-commands
+            let stream = collect_stream("../resources/test_db/postgres.sql", fast_scan, Dialect::Postgres);
+            let statements = blocks(&stream);
+            assert!(statements[0].starts_with("\\restrict "));
+            assert!(statements.iter().any(|block| block.starts_with("CREATE FUNCTION") && block.ends_with("END;\n$$;")));
+            assert!(statements.iter().any(|block| block.starts_with("COMMENT ON TABLE") && block.ends_with("buy things';")));
+            let copy: Vec<&String> = statements.iter().filter(|block| block.starts_with("COPY")).collect();
+            assert_eq!(copy.len(), 2);
+            assert!(copy[0].ends_with("gift;\\nwrap it\n4\t4\t7.25\tsay \"hi\"\n\\."), "{}", copy[0]);
+            assert_eq!(concat(stream), std::fs::read("../resources/test_db/postgres.sql").unwrap());
+
+            let stream = collect_stream("../resources/test_db/fn.sql", fast_scan, Dialect::Postgres);
+            assert_eq!(blocks(&stream).len(), 1);
+
+            // statements between DELIMITER ;; and DELIMITER ; end with ;;
+            let stream = collect_stream("../resources/test_db/routines.sql", fast_scan, Dialect::Mysql);
+            let statements = blocks(&stream);
+            assert_eq!(statements.iter().filter(|block| *block == "DELIMITER ;;").count(), 2);
+            assert_eq!(statements.iter().filter(|block| *block == "DELIMITER ;").count(), 2);
+            assert!(statements.iter().any(|block| block.starts_with("CREATE DEFINER") && block.ends_with("SELECT 'done;' AS status;\nEND ;;")));
+            assert_eq!(concat(stream), std::fs::read("../resources/test_db/routines.sql").unwrap());
+        }
+
+        // the same dump read as MySQL
+        let file = File::open("../resources/test_db/postgres.sql").unwrap();
+        let mut parser = Parser::new(Tokenizer::new(Reader::new(file)));
+        assert!(std::iter::from_fn(|| parser.token_stream().transpose()).any(|item| item.is_err()));
+    }
+
+    #[test]
+    fn mysql_comments(){
+        let stream = collect_stream("../resources/test_db/mariadb.sql", true, Dialect::Mysql);
+        // the sandbox line is for the client, the server never runs it
+        assert_eq!(stream[0], TokenStream::Comment(b"/*M!999999\\- enable the sandbox mode */".to_vec()));
+
+        let statements = blocks(&stream);
+        assert_eq!(statements[0], "/*!40101 SET @OLD_CHARACTER_SET_CLIENT=@@CHARACTER_SET_CLIENT */;");
+        assert!(statements.contains(&String::from("/*!40000 ALTER TABLE `users` DISABLE KEYS */;")));
+        assert!(statements.contains(&String::from("/*M!100616 SET NOTE_VERBOSITY=@OLD_NOTE_VERBOSITY */;")));
+
+        let comments: Vec<&TokenStream> = stream.iter()
+            .filter(|item| matches!(item, TokenStream::Comment(tokens) if tokens.starts_with(b"#")))
+            .collect();
+        assert_eq!(comments, vec![
+            &TokenStream::Comment(b"# rows added by hand after the dump, `users` 'dave'\n".to_vec()),
+            &TokenStream::Comment(b"# the last one\n".to_vec()),
+        ]);
+        assert_eq!(stream.iter().filter(|item| matches!(item, TokenStream::Insert(..))).count(), 2);
+        assert_eq!(concat(stream), std::fs::read("../resources/test_db/mariadb.sql").unwrap());
+
+        // the other dialects have neither
+        let file = File::open("../resources/test_db/mariadb.sql").unwrap();
+        let mut tokenizer = Tokenizer::new(Reader::new(file));
+        tokenizer.set_dialect(Dialect::Ansi);
+        let mut parser = Parser::new(tokenizer);
+        assert!(std::iter::from_fn(|| parser.token_stream().transpose()).any(|item| item.is_err()));
+    }
+
+    #[test]
+    fn mssql_batches(){
+        for fast_scan in [true, false] {
+            let stream = collect_stream("../resources/test_db/mssql.sql", fast_scan, Dialect::Mssql);
+            let blocks = blocks(&stream);
+            assert_eq!(blocks.iter().filter(|block| *block == "GO").count(), 9);
+            assert_eq!(blocks[0], "USE [shop]");
+            assert!(blocks.contains(&String::from("SET IDENTITY_INSERT [dbo].[users] ON ")));
+            assert!(blocks.contains(&String::from(
+                "ALTER TABLE [dbo].[orders]  WITH CHECK ADD  CONSTRAINT [FK_orders_users] FOREIGN KEY([user_id])\r\nREFERENCES [dbo].[users] ([id])"
+            )));
+
+            let inserts: Vec<&Vec<u8>> = stream.iter().filter_map(|item| match item {
+                TokenStream::Insert(tokens, _) => Some(tokens),
+                _ => None,
+            }).collect();
+            assert_eq!(inserts.len(), 8);
+            // the GO inside the string does not end the batch
+            assert!(inserts[2].ends_with(b"N'c;d@example.com\r\nGO on a line of its own', NULL)"));
+            assert!(inserts[3].ends_with(b"N'C:\\temp\\', N'dave@example.com', NULL)"));
+
+            // nothing is lost on the way
+            assert_eq!(concat(stream), std::fs::read("../resources/test_db/mssql.sql").unwrap());
+        }
+    }
+
+    #[test]
+    fn compound_statements(){
+        let path = "../resources/test_db/triggers.sql";
+        for dialect in [Dialect::Sqlite, Dialect::Mysql, Dialect::Ansi] {
+            let stream = collect_stream(path, true, dialect);
+            let statements = blocks(&stream);
+            assert_eq!(statements.len(), 7, "{:?}", dialect);
+            assert!(statements[2].starts_with("CREATE TRIGGER orders_audit") && statements[2].ends_with("WHERE id = NEW.id;\nEND;"));
+            assert!(statements[3].starts_with("CREATE TEMP TRIGGER") && statements[3].ends_with("OLD.id;\nEND;"));
+            // END IF, END LOOP and END CASE do not end the procedure
+            assert!(statements[4].starts_with("CREATE OR REPLACE PROCEDURE") && statements[4].ends_with("  END CASE;\nEND;"));
+            assert_eq!(statements[5], "CREATE FUNCTION twice(n INT) RETURNS INT RETURN n * 2;");
+            assert_eq!(statements[6], "SELECT CASE WHEN 1 THEN 'begin' END;");
+            let kinds: Vec<&StatementKind> = stream.iter().filter_map(|item| match item {
+                TokenStream::Block(_, kind) => Some(kind),
+                _ => None,
+            }).collect();
+            assert_eq!(kinds[2], &StatementKind::CreateRoutine(String::from("orders_audit")));
+            assert_eq!(kinds[6], &StatementKind::Other);
+            assert_eq!(concat(stream), std::fs::read(path).unwrap());
+        }
+    }
+
+    #[test]
+    fn nested_comments(){
+        let path = "../resources/test_db/nested-comments.sql";
+        for fast_scan in [true, false] {
+            let stream = collect_stream(path, fast_scan, Dialect::Postgres);
+            let comments: Vec<String> = stream.iter().filter_map(|item| match item {
+                TokenStream::Comment(tokens) if tokens.starts_with(b"/*") => Some(String::from_utf8_lossy(tokens).into_owned()),
+                _ => None,
+            }).collect();
+            assert_eq!(comments.len(), 2);
+            assert_eq!(comments[0], "/* outer /* inner */ still a comment; DROP TABLE hello; */");
+            assert!(comments[1].ends_with("INSERT INTO hello VALUES (0, 'zero');\n */"));
+
+            let statements = blocks(&stream);
+            assert_eq!(statements.len(), 1);
+            assert!(statements[0].contains("NOT NULL /* the /* key */ of a row */,\n"));
+            assert_eq!(stream.iter().filter(|item| matches!(item, TokenStream::ValuesTuple(_))).count(), 1);
+            assert_eq!(concat(stream), std::fs::read(path).unwrap());
+        }
+
+        // MySQL ends the comment at the first */ and trips over the second one
+        let file = File::open(path).unwrap();
+        let mut parser = Parser::new(Tokenizer::new(Reader::new(file)));
+        let error = std::iter::from_fn(|| parser.token_stream().transpose()).find_map(|item| item.err()).unwrap();
+        assert_eq!(error.pos, 132);
+    }
+
 
 }
\ No newline at end of file
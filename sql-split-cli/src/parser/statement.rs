@@ -0,0 +1,514 @@
+// reads one plain or quoted word starting at `index`. quotes are dropped.
+pub fn word_at(tokens: &[u8], index: usize) -> (String, usize) {
+    match tokens.get(index) {
+        Some(quote @ (b'`' | b'"' | b'[')) => {
+            let closing = if *quote == b'[' { b']' } else { *quote };
+            let end = tokens[index + 1..].iter()
+                .position(|byte| *byte == closing)
+                .map_or(tokens.len(), |end| index + 1 + end);
+            (String::from_utf8_lossy(&tokens[index + 1..end]).into_owned(), end + 1)
+        },
+        _ => {
+            let end = tokens[index..].iter()
+                .position(|byte| byte.is_ascii_whitespace() || matches!(byte, b'(' | b';' | b','))
+                .map_or(tokens.len(), |end| index + end);
+            (String::from_utf8_lossy(&tokens[index..end]).into_owned(), end.max(index + 1))
+        },
+    }
+}
+
+// splits the start of a statement into at most `limit` words
+pub fn words(tokens: &[u8], limit: usize) -> Vec<String> {
+    let mut words = vec![];
+    let mut index = 0;
+    while index < tokens.len() && words.len() < limit {
+        match tokens[index] {
+            byte if byte.is_ascii_whitespace() => index += 1,
+            b'(' | b';' | b',' => break,
+            _ => {
+                let (mut word, mut end) = word_at(tokens, index);
+                // `db`.`table`
+                while tokens.get(end) == Some(&b'.') {
+                    let (next, next_end) = word_at(tokens, end + 1);
+                    word.push('.');
+                    word.push_str(&next);
+                    end = next_end;
+                }
+                words.push(word);
+                index = end;
+            },
+        }
+    }
+    words
+}
+
+fn is(word: Option<&String>, keyword: &str) -> bool {
+    word.is_some_and(|word| word.eq_ignore_ascii_case(keyword))
+}
+
+// name of the table a statement works on, if it is about one table:
+// INSERT/REPLACE [INTO], CREATE/DROP/ALTER/TRUNCATE TABLE and LOCK TABLES.
+// a `db`.`table` name is returned as `db.table`.
+pub fn table_name(tokens: &[u8]) -> Option<String> {
+    let words = words(conditional_body(tokens), 8);
+    let mut words = words.iter().peekable();
+    let first = words.next()?.to_lowercase();
+    match first.as_str() {
+        "insert" | "replace" => {
+            // INSERT IGNORE INTO `t`, INSERT [dbo].[t]
+            const MODIFIERS: [&str; 5] = ["low_priority", "delayed", "high_priority", "ignore", "into"];
+            words.find(|word| !MODIFIERS.iter().any(|modifier| word.eq_ignore_ascii_case(modifier))).cloned()
+        },
+        "create" | "drop" | "alter" | "truncate" => {
+            // CREATE TEMPORARY TABLE, TRUNCATE `x`
+            for word in words.by_ref() {
+                if word.eq_ignore_ascii_case("table") {
+                    break
+                }
+                if first == "truncate" {
+                    return Some(word.clone())
+                }
+                if !is(Some(word), "temporary") {
+                    return None
+                }
+            }
+
+            // IF [NOT] EXISTS
+            if is(words.peek().copied(), "if") {
+                words.next();
+                if is(words.peek().copied(), "not") {
+                    words.next();
+                }
+                words.next();
+            }
+            // ALTER TABLE ONLY of pg_dump
+            if is(words.peek().copied(), "only") {
+                words.next();
+            }
+            words.next().cloned()
+        },
+        "lock" => {
+            if is(words.next(), "tables") {
+                words.next().cloned()
+            }else{
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
+// what a statement does, told by its leading keywords. carries the name
+// of the table or other object it works on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementKind {
+    CreateTable(String),
+    AlterTable(String),
+    DropTable(String),
+    TruncateTable(String),
+    // the table the index is on
+    CreateIndex(String),
+    CreateView(String),
+    // CREATE TRIGGER, PROCEDURE or FUNCTION
+    CreateRoutine(String),
+    // DROP of anything but a table, e.g. DROP VIEW
+    Drop(String),
+    LockTables(String),
+    UnlockTables,
+    // REPLACE INTO is not read as an INSERT
+    Replace(String),
+    // COPY ... FROM stdin; with its data
+    Copy(String),
+    // SET statements configure the session
+    Set,
+    Use(String),
+    BeginTransaction,
+    EndTransaction,
+    // the new statement terminator
+    Delimiter(String),
+    // GO line of a SQL Server script
+    BatchSeparator,
+    Other,
+}
+
+impl StatementKind {
+    pub fn of(tokens: &[u8]) -> Self {
+        let words = words(conditional_body(tokens), 16);
+        let first = words.first().map(|word| word.to_lowercase()).unwrap_or_default();
+        if let Some(table) = table_name(tokens) {
+            match first.as_str() {
+                "create" => return Self::CreateTable(table),
+                "alter" => return Self::AlterTable(table),
+                "drop" => return Self::DropTable(table),
+                "truncate" => return Self::TruncateTable(table),
+                "lock" => return Self::LockTables(table),
+                "replace" => return Self::Replace(table),
+                _ => {},
+            }
+        }
+
+        let name = |index: usize| words.get(index).cloned();
+        let kind = match first.as_str() {
+            "create" => created(&words).and_then(|(object, rest)| match object.as_str() {
+                "index" => {
+                    let on = rest.iter().position(|word| word.eq_ignore_ascii_case("on"))?;
+                    rest.get(on + 1).cloned().map(Self::CreateIndex)
+                },
+                "view" => object_name(rest).map(Self::CreateView),
+                "trigger" | "procedure" | "function" => object_name(rest).map(Self::CreateRoutine),
+                _ => None,
+            }),
+            "drop" => words.get(2..).and_then(object_name).map(Self::Drop),
+            "unlock" => Some(Self::UnlockTables),
+            "set" => Some(Self::Set),
+            "use" => name(1).map(Self::Use),
+            "copy" => name(1).map(Self::Copy),
+            "delimiter" => delimiter(tokens).map(|delimiter| Self::Delimiter(String::from_utf8_lossy(delimiter).into_owned())),
+            _ if begins_transaction(tokens) => Some(Self::BeginTransaction),
+            _ if ends_transaction(tokens) => Some(Self::EndTransaction),
+            _ if batch_separator(tokens) => Some(Self::BatchSeparator),
+            _ => None,
+        };
+        kind.unwrap_or(Self::Other)
+    }
+
+    // the table the statement works on
+    pub fn table(&self) -> Option<&str> {
+        match self {
+            Self::CreateTable(table) |
+            Self::AlterTable(table) |
+            Self::DropTable(table) |
+            Self::TruncateTable(table) |
+            Self::CreateIndex(table) |
+            Self::LockTables(table) |
+            Self::Replace(table) |
+            Self::Copy(table) => Some(table),
+            _ => None,
+        }
+    }
+
+    // `CREATE TABLE`, `DROP TABLE`, `ALTER TABLE` or `TRUNCATE TABLE` when
+    // the statement changes the structure of a table
+    pub fn ddl(&self) -> Option<&'static str> {
+        match self {
+            Self::CreateTable(_) => Some("CREATE TABLE"),
+            Self::AlterTable(_) => Some("ALTER TABLE"),
+            Self::DropTable(_) => Some("DROP TABLE"),
+            Self::TruncateTable(_) => Some("TRUNCATE TABLE"),
+            _ => None,
+        }
+    }
+}
+
+// what a CREATE makes, in lower case, and the words after it. e.g. `view`
+// for CREATE OR REPLACE DEFINER=`root`@`%` VIEW
+fn created(words: &[String]) -> Option<(String, &[String])> {
+    const MODIFIERS: [&str; 17] = [
+        "or", "replace", "temp", "temporary", "unique", "aggregate", "constraint", "editionable", "noneditionable",
+        "recursive", "materialized", "fulltext", "spatial", "sql", "security", "definer", "invoker",
+    ];
+    if !is(words.first(), "create") {
+        return None
+    }
+
+    let index = words.iter().skip(1).position(|word| {
+        let word = word.to_lowercase();
+        !MODIFIERS.contains(&word.as_str()) && !word.starts_with("definer=") && !word.starts_with("algorithm=")
+    })? + 1;
+    Some((words[index].to_lowercase(), &words[index + 1..]))
+}
+
+// the name after the kind of object, past IF [NOT] EXISTS
+fn object_name(words: &[String]) -> Option<String> {
+    let mut words = words.iter().peekable();
+    if is(words.peek().copied(), "if") {
+        words.next();
+        if is(words.peek().copied(), "not") {
+            words.next();
+        }
+        words.next();
+    }
+    words.next().cloned()
+}
+
+// first word of a statement in upper case, the one inside the comment
+// for /*!40101 SET NAMES utf8 */;
+pub fn first_keyword(tokens: &[u8]) -> Option<String> {
+    words(conditional_body(tokens), 1).first().map(|word| word.to_uppercase())
+}
+
+// what MySQL runs of a statement in a conditional comment, the whole
+// statement when it is not in one
+pub fn conditional_body(tokens: &[u8]) -> &[u8] {
+    let trimmed = tokens.trim_ascii_start();
+    match trimmed.strip_prefix(b"/*!").or_else(|| trimmed.strip_prefix(b"/*M!")) {
+        Some(body) => {
+            let body = &body[body.iter().take_while(|byte| byte.is_ascii_digit()).count()..];
+            let end = body.windows(2).position(|end| end == b"*/").unwrap_or(body.len());
+            &body[..end]
+        },
+        None => tokens,
+    }
+}
+
+// BEGIN [TRANSACTION|TRAN] or START TRANSACTION
+pub fn begins_transaction(tokens: &[u8]) -> bool {
+    let words = words(tokens, 2);
+    match first_keyword(tokens).as_deref() {
+        Some("BEGIN") => words.len() == 1 || is(words.get(1), "transaction") || is(words.get(1), "tran") || is(words.get(1), "work") ||
+            is(words.get(1), "deferred") || is(words.get(1), "immediate") || is(words.get(1), "exclusive"),
+        Some("START") => is(words.get(1), "transaction"),
+        _ => false,
+    }
+}
+
+// COMMIT, END [TRANSACTION] or ROLLBACK, not ROLLBACK TO a savepoint
+pub fn ends_transaction(tokens: &[u8]) -> bool {
+    let words = words(tokens, 2);
+    match first_keyword(tokens).as_deref() {
+        Some("COMMIT") | Some("END") => true,
+        Some("ROLLBACK") => !is(words.get(1), "to"),
+        _ => false,
+    }
+}
+
+// GO [count] on a line of its own, ends a batch of a SQL Server script
+pub fn batch_separator(line: &[u8]) -> bool {
+    let line = line.trim_ascii();
+    line.len() >= 2 && line[..2].eq_ignore_ascii_case(b"go") &&
+        (line.len() == 2 || line[2].is_ascii_whitespace()) &&
+        line[2..].trim_ascii().iter().all(u8::is_ascii_digit)
+}
+
+// CREATE [OR REPLACE] [TEMP] TRIGGER, PROCEDURE or FUNCTION. the body of
+// these may be a BEGIN ... END block of statements.
+pub fn creates_routine(tokens: &[u8]) -> bool {
+    created(&words(tokens, 16)).is_some_and(|(object, _)| matches!(object.as_str(), "trigger" | "procedure" | "function"))
+}
+
+// COPY ... FROM stdin; of a pg_dump, its rows follow on the next lines
+pub fn copy_from_stdin(tokens: &[u8]) -> bool {
+    first_keyword(tokens).as_deref() == Some("COPY") &&
+        tokens.trim_ascii_end().strip_suffix(b";").unwrap_or(tokens).trim_ascii_end()
+            .to_ascii_lowercase().ends_with(b"from stdin")
+}
+
+// the new statement terminator of a DELIMITER command
+pub fn delimiter(tokens: &[u8]) -> Option<&[u8]> {
+    if first_keyword(tokens).as_deref() == Some("DELIMITER") {
+        Some(tokens[b"DELIMITER".len()..].trim_ascii())
+    }else{
+        None
+    }
+}
+
+// splits the list in the first parenthesis of `tokens` on the commas
+// that are not nested or quoted
+pub fn list(tokens: &[u8]) -> Vec<&[u8]> {
+    list_at(tokens).map(|(items, _, _)| items).unwrap_or_default()
+}
+
+// same as list, also returns where the parenthesis open and close
+pub fn list_at(tokens: &[u8]) -> Option<(Vec<&[u8]>, usize, usize)> {
+    let mut items = vec![];
+    let open = tokens.iter().position(|byte| *byte == b'(')?;
+    let start = open + 1;
+
+    let mut depth = 0;
+    let mut quote = None;
+    let mut item = start;
+    let mut index = start;
+    while index < tokens.len() {
+        let byte = tokens[index];
+        match quote {
+            Some(b'\'') | Some(b'"') if byte == b'\\' => index += 1,
+            Some(closing) if byte == closing => quote = None,
+            Some(_) => {},
+            None => match byte {
+                b'\'' | b'"' | b'`' => quote = Some(byte),
+                b'(' => depth += 1,
+                b')' if depth == 0 => {
+                    items.push(tokens[item..index].trim_ascii());
+                    return Some((items, open, index))
+                },
+                b')' => depth -= 1,
+                b',' if depth == 0 => {
+                    items.push(tokens[item..index].trim_ascii());
+                    item = index + 1;
+                },
+                _ => {},
+            },
+        }
+        index += 1;
+    }
+    None
+}
+
+// words of a column or key definition. parenthesis and quotes stay with
+// their word, `decimal(10, 2)` is one word.
+pub fn definition_words(definition: &[u8]) -> Vec<&[u8]> {
+    let mut words = vec![];
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = None;
+    let mut index = 0;
+    while index < definition.len() {
+        let byte = definition[index];
+        match quote {
+            Some(b'\'') | Some(b'"') if byte == b'\\' => index += 1,
+            Some(closing) if byte == closing => quote = None,
+            Some(_) => {},
+            None => match byte {
+                b'\'' | b'"' | b'`' => quote = Some(byte),
+                b'(' => depth += 1,
+                b')' => depth -= 1,
+                _ if byte.is_ascii_whitespace() && depth == 0 => {
+                    if let Some(start) = start.take() {
+                        words.push(&definition[start..index]);
+                    }
+                    index += 1;
+                    continue;
+                },
+                _ => {},
+            },
+        }
+        start.get_or_insert(index);
+        index += 1;
+    }
+    if let Some(start) = start {
+        words.push(&definition[start..]);
+    }
+    words
+}
+
+// column list of an INSERT header, `INSERT INTO t (`a`, `b`) VALUES`
+pub fn insert_columns(insert_stmt: &[u8]) -> Option<Vec<String>> {
+    let values = insert_stmt.windows(6).rposition(|word| word.eq_ignore_ascii_case(b"values"))?;
+    let columns = list(&insert_stmt[..values]);
+    if columns.is_empty() {
+        return None
+    }
+    Some(columns.iter().map(|column| word_at(column, 0).0).collect())
+}
+
+// a values tuple without the separators around it
+pub fn row(tuple: &[u8]) -> &[u8] {
+    let tuple = tuple.trim_ascii();
+    let tuple = tuple.strip_suffix(b",").or_else(|| tuple.strip_suffix(b";")).unwrap_or(tuple);
+    tuple.trim_ascii()
+}
+
+
+#[cfg(test)]
+mod statement_test{
+    use super::{batch_separator, begins_transaction, creates_routine, ends_transaction, first_keyword, insert_columns, table_name, StatementKind};
+
+    #[test]
+    fn names(){
+        let cases: [(&[u8], Option<&str>); 13] = [
+            (b"INSERT INTO `hello` VALUES (1);", Some("hello")),
+            (b"insert ignore into tokens(`id`) values", Some("tokens")),
+            (b"CREATE TABLE IF NOT EXISTS `access_tokens` (\n `id` int);", Some("access_tokens")),
+            (b"CREATE TABLE `shop`.`users` (`id` int);", Some("shop.users")),
+            (b"DROP TABLE IF EXISTS `users`;", Some("users")),
+            (b"ALTER TABLE orders ADD KEY (`x`);", Some("orders")),
+            (b"ALTER TABLE ONLY public.users\n    ADD CONSTRAINT users_pkey PRIMARY KEY (id);", Some("public.users")),
+            (b"LOCK TABLES `users` WRITE;", Some("users")),
+            (b"TRUNCATE `users`;", Some("users")),
+            (b"SET FOREIGN_KEY_CHECKS=0;", None),
+            (b"CREATE INDEX idx ON users (id);", None),
+            (b"INSERT [dbo].[users] ([id], [name]) VALUES (1, N'a')", Some("dbo.users")),
+            (b"CREATE TABLE [dbo].[order items](\r\n\t[id] [int] NOT NULL\r\n)", Some("dbo.order items")),
+        ];
+
+        for (statement, expected) in cases {
+            assert_eq!(table_name(statement).as_deref(), expected, "{}", String::from_utf8_lossy(statement));
+        }
+    }
+
+    #[test]
+    fn kinds(){
+        let ddl = |tokens: &[u8]| StatementKind::of(tokens).ddl();
+        assert_eq!(ddl(b"CREATE TABLE IF NOT EXISTS `x` (`id` int);"), Some("CREATE TABLE"));
+        assert_eq!(ddl(b"truncate `x`;"), Some("TRUNCATE TABLE"));
+        assert_eq!(ddl(b"CREATE INDEX idx ON users (id);"), None);
+        let is_set = |tokens: &[u8]| StatementKind::of(tokens) == StatementKind::Set;
+        assert!(is_set(b"SET FOREIGN_KEY_CHECKS=0;"));
+        assert!(is_set(b"set\n@a = 1;"));
+        assert!(!is_set(b"SETTINGS;"));
+        assert!(is_set(b"/*!40101 SET NAMES utf8mb4 */;"));
+        assert_eq!(first_keyword(b"/*!50003 CREATE*/ /*!50017 DEFINER=`root`@`%`*/ /*!50003 TRIGGER t */;;").as_deref(), Some("CREATE"));
+
+        assert!(creates_routine(b"CREATE TRIGGER t AFTER UPDATE ON x BEGIN"));
+        assert!(creates_routine(b"create or replace procedure p() begin"));
+        assert!(creates_routine(b"CREATE DEFINER=`root`@`localhost` FUNCTION `f`(n INT) RETURNS INT"));
+        assert!(creates_routine(b"CREATE TEMP TRIGGER t"));
+        assert!(!creates_routine(b"CREATE TABLE trigger (id int)"));
+        assert!(!creates_routine(b"BEGIN TRANSACTION"));
+    }
+
+    #[test]
+    fn statement_kinds(){
+        let name = String::from;
+        let cases: [(&[u8], StatementKind); 20] = [
+            (b"CREATE TABLE `users` (`id` int);", StatementKind::CreateTable(name("users"))),
+            (b"ALTER TABLE orders ADD KEY (`x`);", StatementKind::AlterTable(name("orders"))),
+            (b"/*!40000 ALTER TABLE `users` DISABLE KEYS */;", StatementKind::AlterTable(name("users"))),
+            (b"DROP TABLE IF EXISTS `users`;", StatementKind::DropTable(name("users"))),
+            (b"TRUNCATE `users`;", StatementKind::TruncateTable(name("users"))),
+            (b"CREATE UNIQUE INDEX idx ON public.users USING btree (id);", StatementKind::CreateIndex(name("public.users"))),
+            (b"CREATE INDEX orders_user ON orders(user_id);", StatementKind::CreateIndex(name("orders"))),
+            (b"CREATE OR REPLACE VIEW big_orders AS SELECT 1;", StatementKind::CreateView(name("big_orders"))),
+            (b"CREATE DEFINER=`root`@`localhost` PROCEDURE `order_count`(OUT total INT)", StatementKind::CreateRoutine(name("order_count"))),
+            (b"CREATE TRIGGER IF NOT EXISTS audit AFTER UPDATE ON orders BEGIN", StatementKind::CreateRoutine(name("audit"))),
+            (b"DROP VIEW IF EXISTS `big_orders`;", StatementKind::Drop(name("big_orders"))),
+            (b"LOCK TABLES `users` WRITE;", StatementKind::LockTables(name("users"))),
+            (b"UNLOCK TABLES;", StatementKind::UnlockTables),
+            (b"REPLACE INTO `users` VALUES (1);", StatementKind::Replace(name("users"))),
+            (b"COPY public.orders (id, total) FROM stdin;\n1\t2\n\\.", StatementKind::Copy(name("public.orders"))),
+            (b"USE [shop]", StatementKind::Use(name("shop"))),
+            (b"BEGIN TRANSACTION;", StatementKind::BeginTransaction),
+            (b"DELIMITER ;;", StatementKind::Delimiter(name(";;"))),
+            (b"GO", StatementKind::BatchSeparator),
+            (b"SELECT pg_catalog.set_config('search_path', '', false);", StatementKind::Other),
+        ];
+
+        for (statement, expected) in cases {
+            assert_eq!(StatementKind::of(statement), expected, "{}", String::from_utf8_lossy(statement));
+        }
+        assert_eq!(StatementKind::of(b"CREATE INDEX idx ON users (id);").table(), Some("users"));
+        assert_eq!(StatementKind::of(b"CREATE VIEW v AS SELECT 1;").table(), None);
+        assert_eq!(StatementKind::of(b"COMMIT;"), StatementKind::EndTransaction);
+    }
+
+    #[test]
+    fn columns(){
+        assert_eq!(insert_columns(b"INSERT INTO `users` (`id`, `name`, email) VALUES "),
+            Some(vec![String::from("id"), String::from("name"), String::from("email")]));
+        assert_eq!(insert_columns(b"INSERT INTO `users` VALUES "), None);
+    }
+
+    #[test]
+    fn transactions(){
+        for begin in ["BEGIN TRANSACTION;", "BEGIN;", "START TRANSACTION;", "begin immediate;"] {
+            assert!(begins_transaction(begin.as_bytes()), "{}", begin);
+        }
+        assert!(!begins_transaction(b"BEGIN UPDATE t SET a = 1; END;"));
+        assert!(!begins_transaction(b"COMMIT;"));
+
+        for end in ["COMMIT;", "END TRANSACTION;", "ROLLBACK;", "commit"] {
+            assert!(ends_transaction(end.as_bytes()), "{}", end);
+        }
+        assert!(!ends_transaction(b"ROLLBACK TO SAVEPOINT a;"));
+    }
+
+    #[test]
+    fn batches(){
+        for line in ["GO", "go", "GO 5", "  GO  "] {
+            assert!(batch_separator(line.as_bytes()), "{}", line);
+        }
+        for line in ["GOTO x", "GO;", "GO x", "G", ""] {
+            assert!(!batch_separator(line.as_bytes()), "{}", line);
+        }
+    }
+}
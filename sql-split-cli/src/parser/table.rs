@@ -0,0 +1,277 @@
+use crate::parser::statement::{definition_words, list, list_at, table_name, word_at, words};
+
+// a column of a CREATE TABLE
+#[derive(Debug, PartialEq, Clone)]
+pub struct Column {
+    pub name: String,
+    // as written, e.g. `decimal(10,2) unsigned` or `character varying(20)`
+    pub data_type: String,
+    pub nullable: bool,
+    // as written, e.g. `'0'`, `NULL` or `CURRENT_TIMESTAMP`
+    pub default: Option<String>,
+    // the rest of the definition as written, e.g. `AUTO_INCREMENT COMMENT 'id'`
+    pub extra: String,
+}
+
+// KEY, INDEX, UNIQUE KEY, FULLTEXT KEY ...
+#[derive(Debug, PartialEq, Clone)]
+pub struct Index {
+    pub name: Option<String>,
+    pub unique: bool,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CreateTable {
+    pub name: String,
+    pub columns: Vec<Column>,
+    pub primary_key: Vec<String>,
+    pub indexes: Vec<Index>,
+    // ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 as (`ENGINE`, `InnoDB`) and
+    // (`DEFAULT CHARSET`, `utf8mb4`). values are as written.
+    pub options: Vec<(String, String)>,
+    // definitions that are neither a column nor a key, e.g. FOREIGN KEY
+    // and CHECK constraints, as written
+    pub other: Vec<String>,
+}
+
+// words after the type of a column that start its constraints
+const COLUMN_CONSTRAINTS: [&str; 21] = [
+    "not", "null", "default", "primary", "unique", "key", "auto_increment", "autoincrement", "comment", "collate",
+    "character", "charset", "references", "check", "constraint", "generated", "as", "on", "identity", "invisible", "visible",
+];
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn is(word: Option<&&[u8]>, keyword: &str) -> bool {
+    word.is_some_and(|word| word.eq_ignore_ascii_case(keyword.as_bytes()))
+}
+
+// names in the parenthesis of a key, without their length or order:
+// (`name`(10) DESC, `id`)
+fn key_columns(definition: &[u8]) -> Vec<String> {
+    list(definition).iter().map(|column| word_at(column, 0).0).collect()
+}
+
+impl CreateTable {
+    // None when `tokens` is no CREATE TABLE with a list of columns, e.g.
+    // CREATE TABLE ... LIKE or AS SELECT
+    pub fn parse(tokens: &[u8]) -> Option<Self> {
+        let name = table_name(tokens)?;
+        let (definitions, open, close) = list_at(tokens)?;
+        if !words(&tokens[..open], 16).first().is_some_and(|word| word.eq_ignore_ascii_case("create")) ||
+            words(&tokens[..open], 16).last() != Some(&name) {
+            return None
+        }
+
+        let mut table = CreateTable { name, ..Default::default() };
+        for definition in definitions.into_iter().filter(|definition| !definition.is_empty()) {
+            table.definition(definition);
+        }
+        table.options(&tokens[close + 1..]);
+        Some(table)
+    }
+
+    pub fn column_names(&self) -> Vec<String> {
+        self.columns.iter().map(|column| column.name.clone()).collect()
+    }
+
+    fn definition(&mut self, definition: &[u8]) {
+        let words = definition_words(definition);
+        let quoted = matches!(definition[0], b'`' | b'"' | b'[');
+        let first = if quoted { String::new() } else { text(words[0]).to_lowercase() };
+
+        // CONSTRAINT `name` PRIMARY KEY (`id`)
+        let (key, rest) = match first.as_str() {
+            "constraint" if words.len() > 2 => (text(words[2]).to_lowercase(), &words[3..]),
+            _ => (first.clone(), &words[1..]),
+        };
+
+        match key.as_str() {
+            "primary" => self.primary_key = key_columns(definition),
+            "key" | "index" | "unique" | "fulltext" | "spatial" => {
+                // KEY `name` (`a`), the name is optional
+                let rest = if is(rest.first(), "key") || is(rest.first(), "index") { &rest[1..] } else { rest };
+                let name = if first == "constraint" {
+                    Some(word_at(words[1], 0).0)
+                }else{
+                    rest.first().filter(|word| !word.starts_with(b"(")).map(|word| word_at(word, 0).0)
+                };
+                self.indexes.push(Index {
+                    name,
+                    unique: key == "unique",
+                    columns: key_columns(definition),
+                });
+            },
+            "foreign" | "check" | "constraint" | "exclude" | "period" => self.other.push(text(definition)),
+            _ => self.add_column(definition, &words),
+        }
+    }
+
+    fn add_column(&mut self, definition: &[u8], words: &[&[u8]]) {
+        let name = word_at(definition, 0).0;
+        // the type is the second word and the ones after it up to the first
+        // constraint, `double precision`, `int(10) unsigned`. SQLite columns
+        // may have none.
+        let type_end = words.iter().skip(2)
+            .position(|word| {
+                let keyword = word.split(|byte| *byte == b'(').next().unwrap_or_default();
+                COLUMN_CONSTRAINTS.iter().any(|constraint| keyword.eq_ignore_ascii_case(constraint.as_bytes()))
+            })
+            .map_or(words.len(), |end| end + 2);
+        let data_type = words[1..type_end].iter().map(|word| text(word)).collect::<Vec<_>>().join(" ");
+
+        let mut column = Column { name, data_type, nullable: true, default: None, extra: String::new() };
+        let mut extra = vec![];
+        let mut index = type_end;
+        while index < words.len() {
+            let word = words[index];
+            let next = words.get(index + 1);
+            if word.eq_ignore_ascii_case(b"not") && is(next, "null") {
+                column.nullable = false;
+                index += 1;
+            }else if word.eq_ignore_ascii_case(b"null") {
+                column.nullable = true;
+            }else if word.eq_ignore_ascii_case(b"default") && next.is_some() {
+                column.default = next.map(|value| text(value));
+                index += 1;
+            }else if word.eq_ignore_ascii_case(b"primary") && is(next, "key") {
+                column.nullable = false;
+                self.primary_key = vec![column.name.clone()];
+                index += 1;
+            }else{
+                extra.push(text(word));
+            }
+            index += 1;
+        }
+        column.extra = extra.join(" ");
+        self.columns.push(column);
+    }
+
+    // the table options after the closing parenthesis
+    fn options(&mut self, options: &[u8]) {
+        let options = options.trim_ascii();
+        let options = options.strip_suffix(b";").unwrap_or(options);
+        let mut key = vec![];
+        let mut words = definition_words(options).into_iter().peekable();
+        while let Some(word) = words.next() {
+            if let Some(equals) = word.iter().position(|byte| *byte == b'=') {
+                // ENGINE=InnoDB or ENGINE= InnoDB
+                key.push(text(&word[..equals]));
+                let value = match &word[equals + 1..] {
+                    b"" => words.next().map(text).unwrap_or_default(),
+                    value => text(value),
+                };
+                self.options.push((key.join(" ").trim().to_string(), value));
+                key.clear();
+            }else if words.peek() == Some(&&b"="[..]) {
+                // ENGINE = InnoDB
+                words.next();
+                key.push(text(word));
+                self.options.push((key.join(" "), words.next().map(text).unwrap_or_default()));
+                key.clear();
+            }else{
+                key.push(text(word));
+            }
+        }
+
+        // what is left without a value, e.g. WITHOUT ROWID
+        if !key.is_empty() {
+            self.options.push((key.join(" "), String::new()));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod table_test{
+    use std::fs::File;
+    use crate::dialect::Dialect;
+    use crate::parser::parser::{Parser, TokenStream};
+    use crate::parser::statement::StatementKind;
+
+    use super::{Column, CreateTable, Index};
+
+    fn create_tables(path: &str) -> Vec<CreateTable> {
+        let mut parser = Parser::from_file(File::open(path).unwrap(), true, Dialect::Mysql);
+        let mut tables = vec![];
+        while let Some(item) = parser.token_stream().unwrap() {
+            if let TokenStream::Block(tokens, StatementKind::CreateTable(_)) = item {
+                tables.push(CreateTable::parse(&tokens).unwrap());
+            }
+        }
+        tables
+    }
+
+    fn column(name: &str, data_type: &str, nullable: bool, default: Option<&str>, extra: &str) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default: default.map(String::from),
+            extra: extra.to_string(),
+        }
+    }
+
+    #[test]
+    fn dumps(){
+        let tables = create_tables("../resources/test_db/create-table-with-comments.sql");
+        assert_eq!(tables.len(), 1);
+        let hello = &tables[0];
+        assert_eq!(hello.name, "hello");
+        assert_eq!(hello.columns.len(), 14);
+        assert_eq!(hello.columns[1], column("forum_id", "smallint(5) unsigned", false, Some("'0'"), ""));
+        assert_eq!(hello.columns[13].name, "mod");
+        assert!(hello.primary_key.is_empty());
+        assert_eq!(hello.indexes[1], Index { name: Some(String::from("forum_id")), unique: false, columns: vec![String::from("forum_id")] });
+        assert_eq!(hello.options, vec![
+            (String::from("ENGINE"), String::from("MyISAM")),
+            (String::from("DEFAULT CHARSET"), String::from("latin1")),
+        ]);
+
+        let tables = create_tables("../resources/test_db/small.sql");
+        let tokens = tables.iter().find(|table| table.name == "access_tokens").unwrap();
+        assert_eq!(tokens.column_names(), vec!["id", "user_id", "access_token", "created_at", "updated_at"]);
+        assert_eq!(tokens.columns[0], column("id", "int(10) unsigned", false, None, "AUTO_INCREMENT"));
+        assert_eq!(tokens.columns[2], column("access_token", "varchar(191)", false, None, "COLLATE utf8mb4_unicode_ci"));
+        assert_eq!(tokens.columns[3], column("created_at", "timestamp", true, Some("NULL"), ""));
+        assert_eq!(tokens.primary_key, vec!["id"]);
+        assert_eq!(tokens.indexes.len(), 1);
+        assert_eq!(tokens.other, vec!["CONSTRAINT `access_tokens_user_id_foreign` FOREIGN KEY (`user_id`) REFERENCES `site_users` (`id`)"]);
+        assert_eq!(tokens.options[1], (String::from("AUTO_INCREMENT"), String::from("332")));
+        assert_eq!(tokens.options[3], (String::from("COLLATE"), String::from("utf8mb4_unicode_ci")));
+    }
+
+    #[test]
+    fn definitions(){
+        let create = b"CREATE TABLE `t` (\n  `id` int NOT NULL,\n  `price` decimal(10,2) DEFAULT '0,5' COMMENT 'a (b',\n  \
+            name varchar(5),\n  PRIMARY KEY (`id`),\n  UNIQUE KEY `k` (`name`(3)),\n  `key` int\n) ENGINE=InnoDB;";
+        let table = CreateTable::parse(create).unwrap();
+        assert_eq!(table.column_names(), vec!["id", "price", "name", "key"]);
+        assert_eq!(table.columns[1], column("price", "decimal(10,2)", true, Some("'0,5'"), "COMMENT 'a (b'"));
+        assert_eq!(table.indexes, vec![Index { name: Some(String::from("k")), unique: true, columns: vec![String::from("name")] }]);
+
+        let create = b"CREATE TABLE public.orders (\n    id integer DEFAULT nextval('orders_id_seq'::regclass) NOT NULL,\n    \
+            note character varying(20),\n    created timestamp without time zone,\n    CONSTRAINT orders_pkey PRIMARY KEY (id),\n    \
+            CHECK (id > 0)\n);";
+        let table = CreateTable::parse(create).unwrap();
+        assert_eq!(table.name, "public.orders");
+        assert_eq!(table.columns[0], column("id", "integer", false, Some("nextval('orders_id_seq'::regclass)"), ""));
+        assert_eq!(table.columns[1].data_type, "character varying(20)");
+        assert_eq!(table.columns[2].data_type, "timestamp without time zone");
+        assert_eq!(table.primary_key, vec!["id"]);
+        assert_eq!(table.other, vec!["CHECK (id > 0)"]);
+        assert!(table.options.is_empty());
+
+        let table = CreateTable::parse(b"CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT NOT NULL) WITHOUT ROWID;").unwrap();
+        assert_eq!(table.primary_key, vec!["id"]);
+        assert!(!table.columns[0].nullable);
+        assert_eq!(table.options, vec![(String::from("WITHOUT ROWID"), String::new())]);
+
+        assert_eq!(CreateTable::parse(b"CREATE TABLE t LIKE s;"), None);
+        assert_eq!(CreateTable::parse(b"CREATE TABLE t AS SELECT count(*) FROM s;"), None);
+        assert_eq!(CreateTable::parse(b"DROP TABLE t;"), None);
+    }
+}
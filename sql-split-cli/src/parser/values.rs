@@ -0,0 +1,267 @@
+use std::borrow::Cow;
+
+// one value of a values tuple. borrows from the tuple unless escapes or
+// hex digits had to be decoded.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value<'a> {
+    Null,
+    Int(i64),
+    // 1.5, -1.5e3 and integers too big for Int, as they are written
+    Decimal(&'a [u8]),
+    // a quoted string with the escapes resolved, N'..' and E'..' too
+    String(Cow<'a, [u8]>),
+    // the bytes of 0x1F, X'1F' and _binary '...'
+    Blob(Cow<'a, [u8]>),
+    // anything else as it is written, e.g. NOW() or POINT(1, 2)
+    Expr(&'a [u8]),
+}
+
+// resolves the backslash escapes MySQL writes into strings. \% and \_
+// keep their backslash as they only mean something in LIKE patterns.
+pub fn unescape(byte: u8) -> Vec<u8> {
+    match byte {
+        b'0' => vec![0],
+        b'b' => vec![8],
+        b'n' => vec![b'\n'],
+        b'r' => vec![b'\r'],
+        b't' => vec![b'\t'],
+        b'Z' => vec![26],
+        b'%' | b'_' => vec![b'\\', byte],
+        _ => vec![byte],
+    }
+}
+
+// reads a quoted string starting at `index`. returns it and the index
+// right after the closing quote. the string is borrowed from `tuple`
+// when it holds no escapes. a backslash is only an escape with
+// `backslash_escapes`, like in MySQL.
+pub fn string(tuple: &[u8], index: usize, backslash_escapes: bool) -> Result<(Cow<'_, [u8]>, usize), &'static str> {
+    let quote = tuple[index];
+    let start = index + 1;
+    // only filled once an escape shows up
+    let mut value: Option<Vec<u8>> = None;
+    let mut index = start;
+    while index < tuple.len() {
+        match tuple[index] {
+            b'\\' if backslash_escapes && index + 1 < tuple.len() => {
+                value.get_or_insert_with(|| tuple[start..index].to_vec()).extend(unescape(tuple[index + 1]));
+                index += 2;
+            },
+            // '' inside a string is a single quote
+            byte if byte == quote && tuple.get(index + 1) == Some(&quote) => {
+                value.get_or_insert_with(|| tuple[start..index].to_vec()).push(quote);
+                index += 2;
+            },
+            byte if byte == quote => {
+                let value = match value {
+                    Some(value) => Cow::Owned(value),
+                    None => Cow::Borrowed(&tuple[start..index]),
+                };
+                return Ok((value, index + 1))
+            },
+            byte => {
+                if let Some(value) = value.as_mut() {
+                    value.push(byte);
+                }
+                index += 1;
+            },
+        }
+    }
+    Err("Unclosed string in values tuple.")
+}
+
+// the whole of `value` is one quoted string
+fn quoted(value: &[u8], backslash_escapes: bool) -> Option<Result<Cow<'_, [u8]>, &'static str>> {
+    if !matches!(value.first(), Some(b'\'' | b'"')) {
+        return None
+    }
+    match string(value, 0, backslash_escapes) {
+        Ok((string, end)) if end == value.len() => Some(Ok(string)),
+        Ok(_) => None,
+        Err(e) => Some(Err(e)),
+    }
+}
+
+fn hex_digits(digits: &[u8]) -> Option<Vec<u8>> {
+    if !digits.len().is_multiple_of(2) || !digits.iter().all(u8::is_ascii_hexdigit) {
+        return None
+    }
+    digits.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+// [-]digits[.digits][e[-]digits], `.5` and `1.` too
+fn numeric(value: &[u8]) -> bool {
+    let value = value.strip_prefix(b"-").or_else(|| value.strip_prefix(b"+")).unwrap_or(value);
+    let (mantissa, exponent) = match value.iter().position(|byte| matches!(byte, b'e' | b'E')) {
+        Some(e) => (&value[..e], Some(&value[e + 1..])),
+        None => (value, None),
+    };
+    let mut parts = mantissa.splitn(2, |byte| *byte == b'.');
+    let integer = parts.next().unwrap_or_default();
+    let fraction = parts.next().unwrap_or_default();
+    let exponent = exponent.map(|exponent| exponent.strip_prefix(b"-").or_else(|| exponent.strip_prefix(b"+")).unwrap_or(exponent));
+
+    integer.iter().chain(fraction).all(u8::is_ascii_digit) && integer.len() + fraction.len() > 0 &&
+        exponent.is_none_or(|exponent| !exponent.is_empty() && exponent.iter().all(u8::is_ascii_digit))
+}
+
+// what one comma separated value of a tuple is
+fn value(value: &[u8], backslash_escapes: bool) -> Result<Value<'_>, &'static str> {
+    if value.eq_ignore_ascii_case(b"null") {
+        return Ok(Value::Null)
+    }
+    if let Some(string) = quoted(value, backslash_escapes) {
+        return string.map(Value::String)
+    }
+
+    match value {
+        // N'national', E'escaped' has backslash escapes in any dialect
+        [b'N' | b'n', rest @ ..] if quoted(rest, backslash_escapes).is_some() => {
+            return quoted(rest, backslash_escapes).unwrap().map(Value::String)
+        },
+        [b'E' | b'e', rest @ ..] if quoted(rest, true).is_some() => return quoted(rest, true).unwrap().map(Value::String),
+        [b'X' | b'x', rest @ ..] => {
+            if let Some(Ok(digits)) = quoted(rest, backslash_escapes) {
+                if let Some(bytes) = hex_digits(&digits) {
+                    return Ok(Value::Blob(Cow::Owned(bytes)))
+                }
+            }
+        },
+        [b'0', b'x' | b'X', digits @ ..] => {
+            if let Some(bytes) = hex_digits(digits) {
+                return Ok(Value::Blob(Cow::Owned(bytes)))
+            }
+        },
+        // _binary 'bytes', _utf8mb4 'text'
+        [b'_', ..] => {
+            let introducer = value.iter().take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'_').count();
+            if let Some(string) = quoted(value[introducer..].trim_ascii_start(), backslash_escapes) {
+                let string = string?;
+                return Ok(if value[..introducer].eq_ignore_ascii_case(b"_binary") { Value::Blob(string) } else { Value::String(string) })
+            }
+        },
+        _ => {},
+    }
+
+    if numeric(value) {
+        let int = std::str::from_utf8(value).ok().filter(|_| value.iter().all(|byte| byte.is_ascii_digit() || *byte == b'-'));
+        return Ok(match int.and_then(|int| int.parse().ok()) {
+            Some(int) => Value::Int(int),
+            None => Value::Decimal(value),
+        })
+    }
+    Ok(Value::Expr(value))
+}
+
+// the quote after `before` opens an E'..' string of postgres
+fn escape_string(before: &[u8]) -> bool {
+    match before {
+        [.., prefix, b'E' | b'e'] => !prefix.is_ascii_alphanumeric() && *prefix != b'_',
+        [b'E' | b'e'] => true,
+        _ => false,
+    }
+}
+
+// splits `(1, 'a', NULL)` into its values. commas inside strings or
+// nested parenthesis such as POINT(1, 2) or NOW() do not split.
+// `backslash_escapes` comes from the Syntax of the dialect.
+pub fn parse_tuple(tuple: &[u8], backslash_escapes: bool) -> Result<Vec<Value<'_>>, &'static str> {
+    let tuple = tuple.trim_ascii();
+    let tuple = tuple.strip_prefix(b"(").and_then(|tuple| tuple.strip_suffix(b")"))
+        .ok_or("Values tuple should be wrapped in parenthesis.")?;
+
+    let mut values = vec![];
+    let mut index = 0;
+    loop {
+        let start = index;
+        let mut depth = 0;
+        while index < tuple.len() {
+            match tuple[index] {
+                b',' if depth == 0 => break,
+                b'(' => depth += 1,
+                b')' => depth -= 1,
+                b'\'' | b'"' => {
+                    let escapes = backslash_escapes || escape_string(&tuple[..index]);
+                    index = string(tuple, index, escapes)?.1;
+                    continue;
+                },
+                _ => {},
+            }
+            index += 1;
+        }
+        values.push(value(tuple[start..index].trim_ascii(), backslash_escapes)?);
+
+        if index == tuple.len() {
+            break
+        }
+        // past the `,`
+        index += 1;
+    }
+
+    Ok(values)
+}
+
+
+#[cfg(test)]
+mod values_test{
+    use std::borrow::Cow;
+    use super::{parse_tuple, Value};
+
+    fn string(value: &str) -> Value<'_> {
+        Value::String(Cow::Borrowed(value.as_bytes()))
+    }
+
+    fn expr(value: &str) -> Value<'_> {
+        Value::Expr(value.as_bytes())
+    }
+
+    #[test]
+    fn values(){
+        assert_eq!(parse_tuple(b"(1, 'alice', NULL)", true).unwrap(), vec![Value::Int(1), string("alice"), Value::Null]);
+        assert_eq!(parse_tuple(b"(3,'o\\'brien','it''s','a\\nb\\\\c')", true).unwrap(),
+            vec![Value::Int(3), string("o'brien"), string("it's"), string("a\nb\\c")]);
+        assert_eq!(parse_tuple(b"(POINT(1, 2), 0x1F, 'a, b', -1.5e3)", true).unwrap(),
+            vec![expr("POINT(1, 2)"), Value::Blob(Cow::Borrowed(&[0x1f])), string("a, b"), Value::Decimal(b"-1.5e3")]);
+        assert_eq!(parse_tuple(b"('')", true).unwrap(), vec![string("")]);
+        assert!(parse_tuple(b"(1, 'open)", true).is_err());
+        assert!(parse_tuple(b"1, 2", true).is_err());
+    }
+
+    #[test]
+    fn typed_values(){
+        let values = parse_tuple(b"(-42, 18446744073709551615, .5, NOW(), CONCAT('a,', LOWER('B)')), X'00ff', _binary 'b\\0', N'n', \
+            _utf8mb4'u', 'plain', 'esc\\'d', TRUE)", true).unwrap();
+        assert_eq!(values, vec![
+            Value::Int(-42),
+            Value::Decimal(b"18446744073709551615"),
+            Value::Decimal(b".5"),
+            expr("NOW()"),
+            expr("CONCAT('a,', LOWER('B)'))"),
+            Value::Blob(Cow::Borrowed(&[0, 0xff])),
+            Value::Blob(Cow::Borrowed(b"b\0")),
+            string("n"),
+            string("u"),
+            string("plain"),
+            string("esc'd"),
+            expr("TRUE"),
+        ]);
+
+        // strings without escapes are not copied
+        assert!(matches!(values[9], Value::String(Cow::Borrowed(_))));
+        assert!(matches!(values[10], Value::String(Cow::Owned(_))));
+        assert_eq!(parse_tuple(b"(0x1, 'a' COLLATE utf8_bin, -)", true).unwrap(), vec![expr("0x1"), expr("'a' COLLATE utf8_bin"), expr("-")]);
+    }
+
+    #[test]
+    fn without_backslash_escapes(){
+        // SQLite, Postgres and SQL Server keep the backslash, only '' escapes
+        assert_eq!(parse_tuple(b"(4,'C:\\temp\\','it''s')", false).unwrap(), vec![Value::Int(4), string("C:\\temp\\"), string("it's")]);
+        assert_eq!(parse_tuple(b"(N'a\\n', X'00ff')", false).unwrap(), vec![string("a\\n"), Value::Blob(Cow::Borrowed(&[0, 0xff]))]);
+        // E'..' has them anyway
+        assert_eq!(parse_tuple(b"(E'a\\'b', 'c\\')", false).unwrap(), vec![string("a'b"), string("c\\")]);
+        // read with escapes the quote after the backslash does not end the string
+        assert!(parse_tuple(b"(4,'C:\\temp\\')", true).is_err());
+    }
+}
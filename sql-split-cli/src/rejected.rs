@@ -0,0 +1,62 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::tokenizer::token_err::TokenErr;
+
+pub const REJECTED_FILE: &str = "./rejected.sql";
+
+// copies the input ranges skipped by --on-error quarantine into
+// ./rejected.sql, each one after a comment with its offsets
+pub struct Rejected {
+    input: File,
+    output: Option<BufWriter<File>>,
+    // keep what an interrupted run rejected before
+    append: bool,
+}
+
+impl Rejected {
+    pub fn new(input: &Path, append: bool) -> io::Result<Self> {
+        if !append {
+            // left over from an earlier run
+            let _ = fs::remove_file(REJECTED_FILE);
+        }
+
+        Ok(Self {
+            input: File::open(input)?,
+            output: None,
+            append,
+        })
+    }
+
+    pub fn write(&mut self, err: &TokenErr, start: usize, end: usize) -> io::Result<()> {
+        if self.output.is_none() {
+            let file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .append(self.append)
+                .truncate(!self.append)
+                .open(REJECTED_FILE)?;
+            self.output = Some(BufWriter::new(file));
+        }
+
+        let mut bytes = vec![0; end - start];
+        self.input.seek(SeekFrom::Start(start as u64))?;
+        self.input.read_exact(&mut bytes)?;
+
+        let output = self.output.as_mut().unwrap();
+        writeln!(output, "-- bytes {}..{}: {}", start, end, err)?;
+        output.write_all(&bytes)?;
+        if !bytes.ends_with(b"\n") {
+            output.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    pub fn close(&mut self) -> io::Result<()> {
+        match self.output.take() {
+            Some(mut output) => output.flush(),
+            None => Ok(()),
+        }
+    }
+}
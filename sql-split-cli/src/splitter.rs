@@ -1,15 +1,64 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::path::PathBuf;
 use sql_split_reader::Reader;
 
+use crate::checkpoint::Checkpoint;
+use crate::dialect::Dialect;
+use crate::filter::{Condition, RowFilter};
+use crate::parallel::ParallelParser;
 use crate::parser::parser::TokenStream;
 use crate::parser::parser::Parser;
+use crate::parser::parser::StreamSource;
+use crate::parser::statement::{row, table_name, StatementKind};
+use crate::translate::{Translation, Translator, Untranslated};
 use crate::tokenizer::{
     tokenizer::Tokenizer, 
     token_err::TokenErr, 
 };
 
+// line in front of the COMMIT and BEGIN --chunk-transactions adds, merge
+// and --verify leave out the statement after it
+pub const CHUNK_TRANSACTION: &[u8] = b"-- added by --chunk-transactions\n";
+
+// line in front of the copy of an INSERT header that starts a chunk cut
+// inside the INSERT, merge joins the two halves again
+pub const CHUNK_INSERT: &[u8] = b"-- continues the INSERT of the previous chunk\n";
+
 pub struct SplitterSettings{
     pub write: usize,
     pub file: std::fs::File,
+    pub path: PathBuf,
+    pub fast_scan: bool,
+    // more than one thread parses the file in parallel segments
+    pub threads: usize,
+    // bytes every thread parses at a time
+    pub segment_size: usize,
+    // write the leading SET statements again at the top of every chunk
+    pub replay_preamble: bool,
+    // continue an interrupted split
+    pub resume: Option<Checkpoint>,
+    pub on_error: OnError,
+    // rewrite the data into INSERTs of this many rows
+    pub rows_per_insert: Option<usize>,
+    // SQL flavour of the input
+    pub dialect: Dialect,
+    // translate the statements for another database
+    pub translate: Option<Translation>,
+    // a transaction open at the end of a chunk is committed there and
+    // begun again in the next chunk
+    pub chunk_transactions: bool,
+    // --where, rows that do not match are dropped
+    pub filter: Vec<Condition>,
+}
+
+// what to do with a statement that does not parse
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OnError {
+    Abort,
+    // resync at the next statement and carry on
+    Skip,
+    // same as skip, the bad bytes are kept in ./rejected.sql
+    Quarantine,
 }
 
 
@@ -22,21 +71,73 @@ pub enum FileState{
 impl std::fmt::Display for FileState {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self{
-            FileState::New => write!(f, "{}", "New"),
-            FileState::Continue => write!(f, "{}", "Continue")
+            FileState::New => write!(f, "New"),
+            FileState::Continue => write!(f, "Continue")
         }        
     }
 }
 
+// what went into a single chunk
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ChunkStats {
+    pub statements: usize,
+    pub rows: usize,
+    pub tables: BTreeSet<String>,
+    pub input_start: usize,
+    pub input_end: usize,
+}
+
 pub struct Splitter {
-    parser: Parser,
+    parser: Box<dyn StreamSource>,
     total_bytes: usize,
     max_write_size:usize,
     last_insert: Vec<u8>,
+    // SET statements seen before anything else in the dump
+    preamble: Vec<u8>,
+    in_preamble: bool,
+    replay_preamble: bool,
+    input_size: usize,
+    chunks: usize,
+    // state after every completed chunk the writer has not closed yet.
+    // the first item of a chunk may complete it right away, before the
+    // chunk in front of it is on disk.
+    checkpoints: VecDeque<Checkpoint>,
+    current: ChunkStats,
+    // stats of the completed chunks the writer has not closed yet
+    completed: VecDeque<ChunkStats>,
+    on_error: OnError,
+    // an INSERT of the input is open, its last row ended with `,`
+    open_insert: bool,
+    // `,` and line feeds after the last row of an open INSERT. held back
+    // until the next row shows up because the INSERT may have to be
+    // closed with `;` instead.
+    held: Vec<u8>,
+    rows_per_insert: Option<usize>,
+    // rows in the INSERT being written with --rows-per-insert
+    batch_rows: usize,
+    translator: Option<Translator>,
+    chunk_transactions: bool,
+    // BEGIN statement of the transaction open in the input
+    transaction: Option<Vec<u8>>,
+    // the last chunk committed the open transaction, the next one has
+    // to begin it again
+    reopen: Option<Vec<u8>>,
+    // chunks are only cut between the batches of a SQL Server script
+    batches: bool,
+    // nothing but white space since the last GO line
+    batch_ended: bool,
+    // a DELIMITER command set something else than `;`
+    custom_delimiter: bool,
+    filter: Option<RowFilter>,
+    // the first rows of the INSERT were dropped, the next row that is
+    // kept has to start it again
+    header_pending: bool,
 }
 
 pub enum SplitterState{
     SyntaxErr(TokenErr),
+    // a statement did not parse and the input range was skipped
+    Skipped(TokenErr, usize, usize),
     // Reached output limit. send the chunk
     Chunk(FileState, Vec<u8>),
     // reached the EOF.
@@ -45,15 +146,128 @@ pub enum SplitterState{
 
 impl Splitter {
     pub fn new(settings: SplitterSettings) -> Self {
-        let tokenizer = Tokenizer::new(Reader::new(settings.file));
+        let input_size = settings.file.metadata().map(|meta| meta.len() as usize).unwrap_or(0);
+        let resume = settings.resume.unwrap_or_default();
+        let mut filter = RowFilter::new(settings.filter, settings.dialect);
+        if let Some(filter) = filter.as_mut() {
+            filter.set_columns(resume.filter_columns);
+        }
+        let parser: Box<dyn StreamSource> = if settings.threads > 1 {
+            Box::new(ParallelParser::new(
+                &settings.path,
+                settings.threads,
+                settings.segment_size,
+                settings.fast_scan,
+                settings.dialect,
+                resume.offset,
+            ))
+        }else{
+            let mut reader = Reader::new(settings.file);
+            if resume.offset > 0 {
+                reader.seek(resume.offset);
+            }
+
+            let mut tokenizer = Tokenizer::new(reader);
+            tokenizer.set_fast_scan(settings.fast_scan);
+            tokenizer.set_dialect(settings.dialect);
+            Box::new(Parser::new(tokenizer))
+        };
+
         Self {
-            parser: Parser::new(tokenizer),
+            parser,
             total_bytes: 0,
-            last_insert: vec![],
+            in_preamble: resume.offset == 0,
+            last_insert: resume.last_insert,
+            preamble: resume.preamble,
+            replay_preamble: settings.replay_preamble,
+            input_size,
+            chunks: resume.chunks,
+            checkpoints: VecDeque::new(),
+            current: ChunkStats::default(),
+            completed: VecDeque::new(),
+            on_error: settings.on_error,
+            open_insert: resume.open_insert,
+            held: vec![],
+            rows_per_insert: settings.rows_per_insert,
+            batch_rows: 0,
+            translator: settings.translate.map(|_| resume.translator.unwrap_or_default()),
+            chunk_transactions: settings.chunk_transactions,
+            reopen: resume.transaction.clone().filter(|_| settings.chunk_transactions),
+            transaction: resume.transaction,
+            batches: settings.dialect.syntax().batches,
+            batch_ended: false,
+            custom_delimiter: false,
+            filter,
+            header_pending: false,
             max_write_size: settings.write,
         }
     }
 
+    // what --translate could not translate
+    pub fn untranslated(&self) -> &[Untranslated] {
+        self.translator.as_ref().map_or(&[], |translator| translator.untranslated())
+    }
+
+    // state right after the oldest completed chunk, once. to be saved
+    // when the writer closed that chunk.
+    pub fn completed_checkpoint(&mut self) -> Option<Checkpoint> {
+        self.checkpoints.pop_front()
+    }
+
+    // SET statements repeated at the top of every chunk but the first
+    pub fn replayed_preamble(&self) -> &[u8] {
+        if self.replay_preamble {
+            &self.preamble
+        }else{
+            &[]
+        }
+    }
+
+    // stats of the oldest completed chunk, once
+    pub fn completed_chunk(&mut self) -> Option<ChunkStats> {
+        self.completed.pop_front()
+    }
+
+    fn complete_chunk(&mut self) {
+        // the preamble is what the first chunk starts with, SET statements
+        // after it are replayed like any other statement
+        self.in_preamble = false;
+        self.completed.push_back(std::mem::take(&mut self.current));
+        self.chunks += 1;
+        self.checkpoints.push_back(Checkpoint {
+            input_size: self.input_size,
+            offset: self.parser.position(),
+            chunks: self.chunks,
+            last_insert: self.last_insert.clone(),
+            preamble: self.preamble.clone(),
+            open_insert: self.open_insert,
+            transaction: self.transaction.clone(),
+            filter_columns: self.filter.as_ref().map(|filter| filter.columns().clone()).unwrap_or_default(),
+            translator: self.translator.clone(),
+        });
+    }
+
+    fn track_preamble(&mut self, tokens: &[u8], kind: &StatementKind) {
+        if !self.in_preamble {
+            return
+        }
+
+        if *kind == StatementKind::Set {
+            self.preamble.extend(tokens);
+            self.preamble.push(b'\n');
+        }else{
+            self.in_preamble = false;
+        }
+    }
+
+    fn track_transaction(&mut self, tokens: &[u8], kind: &StatementKind) {
+        match kind {
+            StatementKind::BeginTransaction => self.transaction = Some(tokens.trim_ascii().to_vec()),
+            StatementKind::EndTransaction => self.transaction = None,
+            _ => {},
+        }
+    }
+
     fn file_state(&self, starting_total: usize) -> FileState {
         if starting_total == 0 {
             FileState::New
@@ -62,10 +276,50 @@ impl Splitter {
         }
     }
 
+    // counts a statement or a row towards the current chunk
+    fn track(&mut self, table: Option<String>, statements: usize, rows: usize) {
+        self.current.statements += statements;
+        self.current.rows += rows;
+        if let Some(table) = table {
+            self.current.tables.insert(table);
+        }
+    }
+
     fn send(&mut self, tokens: Vec<u8>, starting_total: usize) -> SplitterState {
+        let mut tokens = if starting_total == 0 && self.chunks > 0 && self.replay_preamble {
+            let mut chunk = self.preamble.clone();
+            chunk.extend(tokens);
+            chunk
+        }else{
+            tokens
+        };
+        if starting_total == 0 {
+            if let Some(reopen) = self.reopen.take() {
+                let mut begin = CHUNK_TRANSACTION.to_vec();
+                begin.extend(reopen);
+                begin.push(b'\n');
+                let start = if self.replay_preamble { self.preamble.len() } else { 0 };
+                tokens.splice(start..start, begin);
+            }
+        }
+
+        self.current.input_end = self.parser.position();
         self.total_bytes += tokens.len();
-        if self.reached_limit(self.total_bytes) {
+        if self.reached_limit(self.total_bytes) && self.can_cut(&tokens) {
+            // maxed out in an INSERT, close the statement. the next chunk
+            // starts with a copy of it.
+            if self.held.first() == Some(&b',') {
+                self.held.remove(0);
+                tokens.push(b';');
+            }
+            if self.chunk_transactions && self.transaction.is_some() {
+                tokens.push(b'\n');
+                tokens.extend(CHUNK_TRANSACTION);
+                tokens.extend(b"COMMIT;\n");
+                self.reopen = self.transaction.clone();
+            }
             self.total_bytes = 0;
+            self.complete_chunk();
         }
 
         SplitterState::Chunk(self.file_state(starting_total), tokens)
@@ -75,47 +329,634 @@ impl Splitter {
         total >= self.max_write_size
     }
 
+    // a batch goes to the server as a whole so it has to stay in one
+    // chunk, the cut waits for the line feed after a GO line. the same
+    // goes for the statements between two DELIMITER commands.
+    fn can_cut(&self, tokens: &[u8]) -> bool {
+        !self.custom_delimiter && (!self.batches || (self.batch_ended && tokens.ends_with(b"\n")))
+    }
+
     fn copy_last_insert(&self, chunk: &mut Vec<u8>) {
         chunk.extend(&self.last_insert)
     }
 
-    fn close_values_tuple(&self, chunk: &mut Vec<u8>){
-        let len = chunk.len() - 1;
-        chunk[len] = b';';
+    // rows end with `,` while the INSERT goes on. the `,` is held back
+    // until it is clear whether another row follows in this chunk.
+    fn send_rows(&mut self, mut tokens: Vec<u8>, starting_total: usize) -> SplitterState {
+        self.open_insert = tokens.last() == Some(&b',');
+        if self.open_insert {
+            self.held.extend(tokens.pop());
+        }
+
+        self.send(tokens, starting_total)
+    }
+
+    // the open INSERT ends early, e.g. the rest of it was skipped
+    fn close_insert(&mut self) {
+        if std::mem::replace(&mut self.open_insert, false) && self.held.first() == Some(&b',') {
+            self.held[0] = b';';
+        }
+    }
+
+    // --rows-per-insert: rows go into INSERTs of `limit` rows no matter
+    // which statements they came from. consecutive INSERTs with the same
+    // header are merged and big ones are cut.
+    fn rebatch(&mut self, header: Vec<u8>, row: &[u8], limit: usize, starting_total: usize) -> SplitterState {
+        let mut ret = vec![];
+        let continues = header == self.last_insert && self.batch_rows < limit &&
+            self.held.first() == Some(&b',') && self.held[1..].iter().all(u8::is_ascii_whitespace);
+
+        if continues {
+            self.batch_rows += 1;
+            self.track(None, 0, 1);
+        }else{
+            self.close_insert();
+            if self.held == b";" {
+                self.held.push(b'\n');
+            }
+            ret.append(&mut self.held);
+            ret.extend(&header);
+            self.batch_rows = 1;
+            self.track(table_name(&header), 1, 1);
+            self.last_insert = header;
+        }
+
+        ret.append(&mut self.held);
+        ret.extend(row);
+        self.open_insert = true;
+        self.held.push(b',');
+        self.send(ret, starting_total)
     }
     
+    // setval statements of --translate, they go before the next statement
+    fn sequences(&mut self, ret: &mut Vec<u8>) {
+        let sequences = self.translator.as_mut().map(Translator::sequences).unwrap_or_default();
+        self.track(None, sequences.len(), 0);
+        for sequence in sequences {
+            ret.extend(sequence.as_bytes());
+            ret.push(b'\n');
+        }
+    }
+
     pub fn process(&mut self) -> SplitterState {
-        let starting_total = self.total_bytes;
-        match self.parser.token_stream() {
-            Ok(Some(item)) => {
-                match item {
-                    TokenStream::Insert(insert_with_values, insert_stmt) => {
-                        self.last_insert = insert_stmt;                        
-                        self.send(insert_with_values, starting_total)
-                    },
-                    TokenStream::ValuesTuple(tokens) => {
-                        let mut ret = vec![];
-                        // starting with fresh collection
-                        // push last insert statement
-                        if starting_total == 0 {
-                            self.copy_last_insert(&mut ret);
+        loop {
+            let starting_total = self.total_bytes;
+            let input_start = self.parser.position();
+            match self.parser.token_stream() {
+                Ok(Some(item)) => {
+                    self.batch_ended = match &item {
+                        TokenStream::Block(_, kind) => *kind == StatementKind::BatchSeparator,
+                        TokenStream::SpaceOrLineFeed(_) => self.batch_ended,
+                        _ => false,
+                    };
+
+                    let item = match self.translator.as_mut() {
+                        Some(translator) => match translator.item(item, input_start) {
+                            Some(item) => item,
+                            None => continue,
+                        },
+                        None => item,
+                    };
+
+                    if starting_total == 0 && self.held.is_empty() {
+                        self.current.input_start = input_start;
+                    }
+
+                    let mut ret = vec![];
+                    match item {
+                        TokenStream::Insert(insert_with_values, insert_stmt) => {
+                            self.in_preamble = false;
+                            let values = &insert_with_values[insert_stmt.len().saturating_sub(1)..];
+                            match self.filter.as_mut().map_or(Ok(true), |filter| filter.keep(&insert_stmt, values)) {
+                                Ok(true) => self.header_pending = false,
+                                Ok(false) => {
+                                    // the first row that is kept starts the
+                                    // INSERT again. a --rows-per-insert batch
+                                    // of another table ends here.
+                                    if insert_stmt != self.last_insert {
+                                        self.close_insert();
+                                    }
+                                    self.last_insert = insert_stmt;
+                                    self.header_pending = true;
+                                    continue;
+                                },
+                                Err(text) => return SplitterState::SyntaxErr(TokenErr { text, pos: input_start }),
+                            }
+
+                            if let Some(limit) = self.rows_per_insert {
+                                return self.rebatch(insert_stmt, row(values), limit, starting_total)
+                            }
+
+                            self.track(table_name(&insert_stmt), 1, 1);
+                            self.last_insert = insert_stmt;
+                            ret.append(&mut self.held);
+                            ret.extend(insert_with_values);
+                            return self.send_rows(ret, starting_total)
+                        },
+                        TokenStream::ValuesTuple(tokens) => {
+                            match self.filter.as_mut().map_or(Ok(true), |filter| filter.keep(&self.last_insert, &tokens)) {
+                                Ok(true) => {},
+                                Ok(false) => {
+                                    // the last row of the INSERT is dropped,
+                                    // the row written before ends it
+                                    if self.rows_per_insert.is_none() && !tokens.trim_ascii_end().ends_with(b",") {
+                                        self.close_insert();
+                                    }
+                                    continue;
+                                },
+                                Err(text) => return SplitterState::SyntaxErr(TokenErr { text, pos: input_start }),
+                            }
+
+                            if let Some(limit) = self.rows_per_insert {
+                                return self.rebatch(self.last_insert.clone(), row(&tokens), limit, starting_total)
+                            }
+
+                            // starting with fresh collection or after
+                            // dropped rows, push last insert statement
+                            let restart = std::mem::take(&mut self.header_pending);
+                            if restart {
+                                ret.append(&mut self.held);
+                            }
+                            if starting_total == 0 || restart {
+                                if !restart {
+                                    ret.extend(CHUNK_INSERT);
+                                }
+                                self.copy_last_insert(&mut ret);
+                                self.track(table_name(&self.last_insert), 1, 0);
+                            }
+                            self.current.rows += 1;
+
+                            ret.append(&mut self.held);
+                            ret.extend(tokens);
+                            return self.send_rows(ret, starting_total)
+                        },
+                        TokenStream::Block(tokens, kind) => {
+                            self.close_insert();
+                            self.header_pending = false;
+                            if let (Some(filter), StatementKind::CreateTable(table)) = (self.filter.as_mut(), &kind) {
+                                filter.create_table(table, &tokens);
+                            }
+                            if let StatementKind::Delimiter(delimiter) = &kind {
+                                self.custom_delimiter = delimiter != ";";
+                            }
+                            self.track_preamble(&tokens, &kind);
+                            self.track_transaction(&tokens, &kind);
+                            self.track(kind.table().map(String::from), 1, 0);
+                            ret.append(&mut self.held);
+                            self.sequences(&mut ret);
+                            ret.extend(tokens);
+                            return self.send(ret, starting_total)
+                        },
+                        TokenStream::Comment(tokens) |
+                        TokenStream::SpaceOrLineFeed(tokens) => {
+                            if self.open_insert {
+                                self.held.extend(tokens);
+                                continue;
+                            }
+                            ret.append(&mut self.held);
+                            ret.extend(tokens);
+                            return self.send(ret, starting_total)
+                        },
+                    }
+                },
+                Ok(None) => {
+                    self.close_insert();
+                    let mut held = std::mem::take(&mut self.held);
+                    self.sequences(&mut held);
+                    self.held = held;
+                    if !self.held.is_empty() {
+                        let held = std::mem::take(&mut self.held);
+                        return self.send(held, starting_total)
+                    }
+
+                    if self.total_bytes > 0 {
+                        self.total_bytes = 0;
+                        self.completed.push_back(std::mem::take(&mut self.current));
+                    }
+                    return SplitterState::Done
+                },
+                Err(e) => {
+                    if self.on_error == OnError::Abort {
+                        return SplitterState::SyntaxErr(e)
+                    }
+
+                    let (start, end) = self.parser.recover();
+                    self.close_insert();
+                    return SplitterState::Skipped(e, start, end)
+                },
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+pub(crate) mod splitter_test{
+    use std::{env, fs};
+    use std::fs::File;
+    use std::path::PathBuf;
+    use crate::checkpoint::Checkpoint;
+    use crate::dialect::Dialect;
+    use crate::filter::Condition;
+    use crate::parallel::SEGMENT_SIZE;
+    use crate::translate::Translation;
+    use crate::verify::summarize_input;
+
+    use super::{FileState, OnError, Splitter, SplitterSettings, SplitterState};
+
+    // a single threaded split of a MySQL dump into 1mb chunks, tests
+    // change what they need with `..test_settings(path)`
+    pub(crate) fn test_settings(path: &str) -> SplitterSettings {
+        SplitterSettings {
+            write: 1024 * 1024,
+            file: File::open(path).unwrap(),
+            path: PathBuf::from(path),
+            fast_scan: true,
+            threads: 1,
+            segment_size: SEGMENT_SIZE,
+            replay_preamble: false,
+            resume: None,
+            on_error: OnError::Abort,
+            rows_per_insert: None,
+            dialect: Dialect::Mysql,
+            translate: None,
+            chunk_transactions: false,
+            filter: vec![],
+        }
+    }
+
+    fn splitter(path: &str, write: usize, replay_preamble: bool, resume: Option<Checkpoint>) -> Splitter {
+        Splitter::new(SplitterSettings { write, replay_preamble, resume, ..test_settings(path) })
+    }
+
+    // returns every chunk and the checkpoint taken after chunk `stop_after`
+    fn chunks(splitter: &mut Splitter, stop_after: usize) -> (Vec<Vec<u8>>, Option<Checkpoint>) {
+        let mut chunks: Vec<Vec<u8>> = vec![];
+        let mut checkpoint = None;
+        loop {
+            match splitter.process() {
+                SplitterState::Chunk(file_state, tokens) => {
+                    if file_state == FileState::New || chunks.is_empty() {
+                        // the previous chunk is written, like in main
+                        if !chunks.is_empty() {
+                            let completed = splitter.completed_checkpoint();
+                            if chunks.len() == stop_after {
+                                checkpoint = completed;
+                            }
                         }
+                        chunks.push(vec![]);
+                    }
+                    chunks.last_mut().unwrap().extend(tokens);
+                },
+                SplitterState::SyntaxErr(e) |
+                SplitterState::Skipped(e, ..) => panic!("{}", e.text),
+                SplitterState::Done => break,
+            }
+        }
+        (chunks, checkpoint)
+    }
+
+    #[test]
+    fn resume_matches_full_run(){
+        let path = "../resources/test_db/big.sql";
+        let (full, checkpoint) = chunks(&mut splitter(path, 50 * 1024, false, None), 3);
+        let checkpoint = checkpoint.unwrap();
+        assert_eq!(checkpoint.chunks, 3);
+        assert!(full.len() > 4);
+
+        let (resumed, _) = chunks(&mut splitter(path, 50 * 1024, false, Some(checkpoint)), 0);
+        assert_eq!(resumed, full[3..].to_vec());
+    }
+
+    #[test]
+    fn resume_after_oversized_item(){
+        // every INSERT is bigger than a chunk and completes the chunk it
+        // starts
+        let path = "../resources/test_db/oversized.sql";
+        let (full, _) = chunks(&mut splitter(path, 1024, false, None), 0);
+        assert_eq!(full.len(), 5);
+        for stop_after in 1..5 {
+            let (_, checkpoint) = chunks(&mut splitter(path, 1024, false, None), stop_after);
+            let checkpoint = checkpoint.unwrap();
+            assert_eq!(checkpoint.chunks, stop_after);
+            assert_eq!(checkpoint.offset, full[..stop_after].concat().len());
+
+            let (resumed, _) = chunks(&mut splitter(path, 1024, false, Some(checkpoint)), 0);
+            assert_eq!(resumed, full[stop_after..].to_vec());
+        }
+    }
+
+    #[test]
+    fn resume_translated(){
+        // the report and the identity columns of the tables split before
+        // the resume point carry over
+        for path in ["../resources/test_db/mysqldump.sql", "../resources/test_db/routines.sql"] {
+            let settings = || SplitterSettings { write: 100, translate: Some(Translation::Postgres), ..test_settings(path) };
+            let mut splitter = Splitter::new(settings());
+            let (full, _) = chunks(&mut splitter, 0);
+            let untranslated = splitter.untranslated().to_vec();
+            assert!(full.len() > 4);
+            for stop_after in 1..full.len() {
+                let (_, checkpoint) = chunks(&mut Splitter::new(settings()), stop_after);
+                let resume = checkpoint.unwrap();
+                let mut splitter = Splitter::new(SplitterSettings { resume: Some(resume), ..settings() });
+                let (resumed, _) = chunks(&mut splitter, 0);
+                assert_eq!(resumed, full[stop_after..].to_vec());
+                assert_eq!(splitter.untranslated(), untranslated);
+            }
+        }
+    }
+
+    #[test]
+    fn replay_preamble(){
+        let path = "../resources/test_db/preamble.sql";
+        let preamble = b"SET SQL_MODE = \"NO_AUTO_VALUE_ON_ZERO\";\nSET time_zone = \"+00:00\";\n";
+        let (full, checkpoint) = chunks(&mut splitter(path, 100, true, None), 1);
+        assert_eq!(checkpoint.unwrap().preamble, preamble.to_vec());
+        assert!(full.len() > 2);
+        for chunk in &full[1..] {
+            assert!(chunk.starts_with(preamble));
+        }
 
-                        ret.extend(tokens);
-                        // maxed out in value tuple close statement
-                        if self.reached_limit(starting_total + ret.len()) {
-                            self.close_values_tuple(&mut ret)
+        let (plain, _) = chunks(&mut splitter(path, 100, false, None), 0);
+        assert!(!plain[1].starts_with(preamble));
+
+        // conditional comments run like any other SET statement
+        let path = "../resources/test_db/mariadb.sql";
+        let (full, checkpoint) = chunks(&mut splitter(path, 1024, true, None), 1);
+        let preamble = checkpoint.unwrap().preamble;
+        assert!(full.len() > 1);
+        assert!(preamble.starts_with(b"/*!40101 SET @OLD_CHARACTER_SET_CLIENT=@@CHARACTER_SET_CLIENT */;\n"));
+        assert!(preamble.ends_with(b"/*M!100616 SET @OLD_NOTE_VERBOSITY=@@NOTE_VERBOSITY, NOTE_VERBOSITY=0 */;\n"));
+        assert!(full[1..].iter().all(|chunk| chunk.starts_with(&preamble)));
+        assert!(!preamble.starts_with(b"/*M!999999"));
+    }
+
+    #[test]
+    fn chunk_stats(){
+        let path = "../resources/test_db/preamble.sql";
+        let mut splitter = splitter(path, 100, false, None);
+        let mut stats = vec![];
+        loop {
+            match splitter.process() {
+                SplitterState::Chunk(..) => stats.extend(splitter.completed_chunk()),
+                SplitterState::SyntaxErr(e) |
+                SplitterState::Skipped(e, ..) => panic!("{}", e.text),
+                SplitterState::Done => break,
+            }
+        }
+        stats.extend(splitter.completed_chunk());
+
+        // input ranges cover the whole file without gaps
+        assert_eq!(stats[0].input_start, 0);
+        for pair in stats.windows(2) {
+            assert_eq!(pair[0].input_end, pair[1].input_start);
+        }
+        assert_eq!(stats.last().unwrap().input_end, std::fs::metadata(path).unwrap().len() as usize);
+
+        assert_eq!(stats.iter().map(|chunk| chunk.rows).sum::<usize>(), 6);
+        assert!(stats.iter().all(|chunk| chunk.tables.iter().all(|table| table == "users")));
+        // SET, SET and CREATE TABLE
+        assert_eq!(stats[0].statements + stats[1].statements, 3);
+    }
+
+    #[test]
+    fn stats_of_oversized_chunks(){
+        // stats are taken when the writer closes a chunk, like in main.
+        // the INSERT that starts a chunk also completes it.
+        let path = "../resources/test_db/oversized.sql";
+        let mut splitter = splitter(path, 1024, false, None);
+        let mut chunks: Vec<usize> = vec![];
+        let mut stats = vec![];
+        loop {
+            match splitter.process() {
+                SplitterState::Chunk(file_state, tokens) => {
+                    if file_state == FileState::New || chunks.is_empty() {
+                        if !chunks.is_empty() {
+                            stats.push(splitter.completed_chunk().unwrap());
                         }
-                        
-                        self.send(ret, starting_total)
-                    },
-                    TokenStream::Block(tokens) => self.send(tokens, starting_total),
-                    TokenStream::Comment(tokens) |
-                    TokenStream::SpaceOrLineFeed(tokens) => self.send(tokens, starting_total),
-                }
-            },
-            Ok(None) => SplitterState::Done,
-            Err(e) => SplitterState::SyntaxErr(e),
-        }
-    }
-}
\ No newline at end of file
+                        chunks.push(0);
+                    }
+                    *chunks.last_mut().unwrap() += tokens.len();
+                },
+                SplitterState::SyntaxErr(e) |
+                SplitterState::Skipped(e, ..) => panic!("{}", e.text),
+                SplitterState::Done => break,
+            }
+        }
+        stats.push(splitter.completed_chunk().unwrap());
+        assert!(splitter.completed_chunk().is_none());
+
+        assert_eq!(stats.len(), chunks.len());
+        let mut start = 0;
+        for (index, (chunk, size)) in stats.iter().zip(&chunks).enumerate() {
+            assert_eq!((chunk.input_start, chunk.input_end), (start, start + size), "chunk {}", index + 1);
+            start += size;
+        }
+        let tables: Vec<Vec<&str>> = stats.iter().map(|chunk| chunk.tables.iter().map(String::as_str).collect()).collect();
+        assert_eq!(tables, vec![vec!["t1"], vec!["t2"], vec!["t3"], vec!["t4"], vec![]]);
+    }
+
+    #[test]
+    fn chunks_end_statements(){
+        let (full, _) = chunks(&mut splitter("../resources/test_db/big.sql", 50 * 1024, false, None), 0);
+        assert!(full.len() > 4);
+        for chunk in &full {
+            let chunk = chunk.trim_ascii();
+            assert!(chunk.ends_with(b";"), "{}", String::from_utf8_lossy(&chunk[chunk.len() - 50..]));
+            assert!(!chunk.starts_with(b"("));
+        }
+    }
+
+    fn skip(path: &str, on_error: OnError) -> (String, Vec<(usize, usize)>) {
+        let mut splitter = Splitter::new(SplitterSettings { on_error, ..test_settings(path) });
+
+        let mut output = vec![];
+        let mut skipped = vec![];
+        loop {
+            match splitter.process() {
+                SplitterState::Chunk(_, tokens) => output.extend(tokens),
+                SplitterState::Skipped(_, start, end) => skipped.push((start, end)),
+                SplitterState::SyntaxErr(_) => break,
+                SplitterState::Done => break,
+            }
+        }
+        (String::from_utf8(output).unwrap(), skipped)
+    }
+
+    #[test]
+    fn skip_broken_statements(){
+        let path = "../resources/test_db/broken.sql";
+        let input = std::fs::read_to_string(path).unwrap();
+        let at = |needle: &str| input.find(needle).unwrap();
+
+        let (output, skipped) = skip(path, OnError::Skip);
+        assert_eq!(skipped, vec![
+            (at("(3,"), at("INSERT INTO `users` VALUES (5")),
+            (at("INSERT INTO `users` VALUES (6"), at("INSERT INTO `users` VALUES (7")),
+        ]);
+        // rows before the broken one are kept and their INSERT is closed
+        assert_eq!(output, format!(
+            "{}(2,'bob');\nINSERT INTO `users` VALUES (5,'eve');\nINSERT INTO `users` VALUES (7,'grace');\n",
+            &input[..at("(2,")]
+        ));
+
+        let (output, skipped) = skip(path, OnError::Abort);
+        assert!(skipped.is_empty());
+        assert!(!output.contains("eve"));
+    }
+
+    #[test]
+    fn chunk_transactions(){
+        let path = "../resources/test_db/sqlite.sql";
+        let settings = |chunk_transactions, resume| SplitterSettings {
+            write: 200,
+            resume,
+            dialect: Dialect::Sqlite,
+            chunk_transactions,
+            ..test_settings(path)
+        };
+
+        let (full, checkpoint) = chunks(&mut Splitter::new(settings(true, None)), 2);
+        assert!(full.len() > 3);
+        for chunk in &full {
+            let chunk = String::from_utf8(chunk.clone()).unwrap();
+            // every chunk begins and commits the transaction on its own
+            assert_eq!(chunk.matches("BEGIN TRANSACTION;").count(), 1, "{}", chunk);
+            assert_eq!(chunk.matches("COMMIT;").count(), 1, "{}", chunk);
+            assert!(chunk.find("BEGIN TRANSACTION;") < chunk.find("COMMIT;"));
+        }
+        // the backslash ends the string in SQLite
+        assert!(full.iter().any(|chunk| String::from_utf8_lossy(chunk).contains("VALUES(4,'C:\\temp\\','dave@example.com',NULL);")));
+
+        let (resumed, _) = chunks(&mut Splitter::new(settings(true, checkpoint)), 0);
+        assert_eq!(resumed, full[2..].to_vec());
+
+        // without the option only the chunks holding the statements have them
+        let (plain, _) = chunks(&mut Splitter::new(settings(false, None)), 0);
+        assert_eq!(plain.concat(), std::fs::read(path).unwrap());
+    }
+
+    #[test]
+    fn mssql_batches(){
+        let path = "../resources/test_db/mssql.sql";
+        let input = std::fs::read_to_string(path).unwrap();
+        let mut splitter = Splitter::new(SplitterSettings { write: 100, dialect: Dialect::Mssql, ..test_settings(path) });
+
+        let (full, _) = chunks(&mut splitter, 0);
+        assert_eq!(full.concat(), input.as_bytes());
+        // every chunk holds whole batches, even when it grows past the limit
+        assert_eq!(full.len(), 7);
+        assert!(full.iter().any(|chunk| chunk.len() > 300));
+        for chunk in &full {
+            assert!(chunk.ends_with(b"\r\nGO\r\n"), "{}", String::from_utf8_lossy(chunk));
+        }
+    }
+
+    #[test]
+    fn delimiter_regions(){
+        let path = "../resources/test_db/routines.sql";
+        let (full, _) = chunks(&mut splitter(path, 1, false, None), 0);
+        assert!(full.len() > 10);
+        // the chunk holding DELIMITER ;; also holds the DELIMITER ; that
+        // goes back to the default
+        for chunk in &full {
+            let chunk = String::from_utf8_lossy(chunk);
+            let custom = chunk.matches("DELIMITER ;;").count();
+            assert_eq!(custom, chunk.matches("DELIMITER ;").count() - custom, "{}", chunk);
+        }
+        assert!(full.iter().any(|chunk| String::from_utf8_lossy(chunk).starts_with("DELIMITER ;;\nCREATE DEFINER")));
+    }
+
+    fn filtered(path: &str, write: usize, rows_per_insert: Option<usize>, filter: &[&str]) -> Splitter {
+        Splitter::new(SplitterSettings {
+            write,
+            rows_per_insert,
+            filter: filter.iter().map(|expr| Condition::parse(expr).unwrap()).collect(),
+            ..test_settings(path)
+        })
+    }
+
+    #[test]
+    fn row_filter(){
+        // the first, a middle and the last row are dropped
+        let path = "../resources/test_db/preamble.sql";
+        let filter = ["users: id != 1", "users: id != 3", "users: `id` < 6"];
+        for (write, rows_per_insert) in [(1024 * 1024, None), (60, None), (1, None), (1024, Some(2)), (60, Some(10))] {
+            let (full, _) = chunks(&mut filtered(path, write, rows_per_insert, &filter), 0);
+            let mut rows = 0;
+            for (index, chunk) in full.iter().enumerate() {
+                let trimmed = chunk.trim_ascii();
+                assert!(!trimmed.ends_with(b",") && !trimmed.ends_with(b")"), "{}", String::from_utf8_lossy(chunk));
+                assert!(!trimmed.starts_with(b"(") && !trimmed.starts_with(b","));
+
+                // every chunk still parses on its own
+                let file = env::temp_dir().join(format!("sql-split-where-{}-{}", write, index));
+                fs::write(&file, chunk).unwrap();
+                rows += summarize_input(&file, Dialect::Mysql).unwrap().rows;
+                fs::remove_file(&file).unwrap();
+            }
+
+            let output = String::from_utf8(full.concat()).unwrap();
+            assert_eq!(rows, 3, "{}", output);
+            for name in ["bob", "dave", "erin"] {
+                assert!(output.contains(name), "{}", output);
+            }
+        }
+
+        let (full, _) = chunks(&mut filtered(path, 1024 * 1024, None, &filter), 0);
+        let output = String::from_utf8(full.concat()).unwrap();
+        assert!(output.contains("\nINSERT INTO `users` (`id`, `name`, `email`) VALUES (2, 'bob', 'bob@example.com'),\n\n(4, 'dave', 'dave@example.com'),\n(5, 'erin', 'erin@example.com');\n\n\nSET"), "{}", output);
+
+        // columns from CREATE TABLE, tables without a condition are kept
+        let path = "../resources/test_db/mysqldump.sql";
+        let (full, _) = chunks(&mut filtered(path, 1024 * 1024, None, &["orders: tenant_id = 42"]), 0);
+        let output = String::from_utf8(full.concat()).unwrap();
+        assert!(output.contains("INSERT INTO `orders` VALUES (1,1,42,19.99,'first order'),(3,1,42,120.50,'gift; wrap it'),(4,3,42,0.99,'');"));
+        assert!(output.contains("(2,'bob',NULL,'2023-01-02 11:30:00')"));
+
+        let mut splitter = filtered(path, 1024 * 1024, None, &["orders: tenant = 42"]);
+        let e = loop {
+            match splitter.process() {
+                SplitterState::SyntaxErr(e) => break e,
+                SplitterState::Done => panic!("unknown column was not reported"),
+                _ => {},
+            }
+        };
+        assert_eq!(e.text, "Column of --where is not in the INSERT or CREATE TABLE of its table.");
+    }
+
+    #[test]
+    fn row_filter_resume(){
+        // the INSERTs without a column list come after the resume point,
+        // their CREATE TABLE before it
+        let path = "../resources/test_db/mysqldump.sql";
+        let filter = || vec![Condition::parse("orders: tenant_id = 42").unwrap()];
+        let settings = || SplitterSettings { write: 200, filter: filter(), ..test_settings(path) };
+        let (full, _) = chunks(&mut Splitter::new(settings()), 0);
+        assert!(full.len() > 4);
+        for stop_after in 1..full.len() {
+            let (_, checkpoint) = chunks(&mut Splitter::new(settings()), stop_after);
+            let resume = checkpoint.unwrap();
+            let (resumed, _) = chunks(&mut Splitter::new(SplitterSettings { resume: Some(resume), ..settings() }), 0);
+            assert_eq!(resumed, full[stop_after..].to_vec());
+        }
+    }
+
+    #[test]
+    fn row_filter_sqlite(){
+        // 'C:\temp\' ends at its quote, the backslash is no escape
+        let path = "../resources/test_db/sqlite.sql";
+        let mut splitter = Splitter::new(SplitterSettings {
+            dialect: Dialect::Sqlite,
+            filter: vec![Condition::parse("users: id > 1").unwrap(), Condition::parse("orders: note != 'x'").unwrap()],
+            ..test_settings(path)
+        });
+        let (full, _) = chunks(&mut splitter, 0);
+        let output = String::from_utf8(full.concat()).unwrap();
+        assert!(!output.contains("alice"), "{}", output);
+        assert!(output.contains("INSERT INTO users VALUES(4,'C:\\temp\\','dave@example.com',NULL);"), "{}", output);
+        // NULL matches no condition
+        assert_eq!(output.matches("INSERT INTO orders").count(), 3, "{}", output);
+    }
+}
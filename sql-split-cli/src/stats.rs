@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+use serde::Serialize;
+
+use crate::parser::parser::{StreamSource, TokenStream};
+use crate::parser::statement::{row, table_name, StatementKind};
+use crate::tokenizer::token_err::TokenErr;
+
+#[derive(Debug, PartialEq, Default, Serialize)]
+pub struct TableStats {
+    pub rows: usize,
+    pub inserts: usize,
+    pub bytes: usize,
+    pub largest_row: usize,
+    // CREATE TABLE, DROP TABLE ... in the order they showed up
+    pub ddl: Vec<String>,
+}
+
+// what is inside a dump, collected by `sql-split stats`
+#[derive(Debug, PartialEq, Default, Serialize)]
+pub struct Stats {
+    pub tables: BTreeMap<String, TableStats>,
+    pub comments: usize,
+    pub set_statements: usize,
+    // statements that are neither about a table nor SET
+    pub unknown_blocks: usize,
+}
+
+impl Stats {
+    pub fn collect(source: &mut dyn StreamSource) -> Result<Self, TokenErr> {
+        let mut stats = Self::default();
+        let mut table = String::new();
+
+        while let Some(item) = source.token_stream()? {
+            match item {
+                TokenStream::Insert(tokens, insert_stmt) => {
+                    table = table_name(&insert_stmt).unwrap_or_default();
+                    let row = row(&tokens[insert_stmt.len().saturating_sub(1)..]).len();
+                    let entry = stats.tables.entry(table.clone()).or_default();
+                    entry.inserts += 1;
+                    entry.rows += 1;
+                    entry.bytes += tokens.len();
+                    entry.largest_row = entry.largest_row.max(row);
+                },
+                TokenStream::ValuesTuple(tokens) => {
+                    let entry = stats.tables.entry(table.clone()).or_default();
+                    entry.rows += 1;
+                    entry.bytes += tokens.len();
+                    entry.largest_row = entry.largest_row.max(row(&tokens).len());
+                },
+                TokenStream::Block(tokens, kind) => {
+                    if kind == StatementKind::Set {
+                        stats.set_statements += 1;
+                    }else if let Some(name) = kind.table() {
+                        // LOCK TABLES, CREATE INDEX, ...
+                        let entry = stats.tables.entry(name.to_string()).or_default();
+                        entry.bytes += tokens.len();
+                        if let Some(ddl) = kind.ddl() {
+                            entry.ddl.push(ddl.to_string());
+                        }
+                    }else if kind != StatementKind::UnlockTables {
+                        stats.unknown_blocks += 1;
+                    }
+                },
+                TokenStream::Comment(_) => stats.comments += 1,
+                TokenStream::SpaceOrLineFeed(_) => {},
+            }
+        }
+
+        Ok(stats)
+    }
+
+    pub fn text(&self) -> String {
+        let mut output = format!(
+            "{:<32} {:>12} {:>10} {:>14} {:>12}  ddl\n",
+            "table", "rows", "inserts", "bytes", "largest row"
+        );
+
+        for (name, table) in &self.tables {
+            output.push_str(&format!(
+                "{:<32} {:>12} {:>10} {:>14} {:>12}  {}\n",
+                name, table.rows, table.inserts, table.bytes, table.largest_row, table.ddl.join(", ")
+            ));
+        }
+
+        output.push_str(&format!("\ncomments: {}\n", self.comments));
+        output.push_str(&format!("set statements: {}\n", self.set_statements));
+        output.push_str(&format!("unknown blocks: {}\n", self.unknown_blocks));
+        output
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod stats_test{
+    use std::fs::File;
+    use crate::dialect::Dialect;
+    use crate::parser::parser::Parser;
+
+    use super::Stats;
+
+    fn stats(path: &str) -> Stats {
+        stats_as(path, Dialect::Mysql)
+    }
+
+    fn stats_as(path: &str, dialect: Dialect) -> Stats {
+        let mut parser = Parser::from_file(File::open(path).unwrap(), true, dialect);
+        Stats::collect(&mut parser).unwrap()
+    }
+
+    #[test]
+    fn small(){
+        let stats = stats("../resources/test_db/small.sql");
+        assert_eq!(stats.comments, 2);
+        assert_eq!(stats.set_statements, 1);
+        assert_eq!(stats.unknown_blocks, 0);
+
+        let hello = &stats.tables["hello"];
+        assert_eq!((hello.rows, hello.inserts), (2, 2));
+        assert_eq!(hello.ddl, vec!["CREATE TABLE"]);
+        assert_eq!(hello.largest_row, "(1, 34, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1)".len());
+
+        let tokens = &stats.tables["tokens"];
+        assert_eq!((tokens.rows, tokens.inserts), (2, 1));
+        assert!(tokens.ddl.is_empty());
+
+        assert_eq!(stats.tables["access_tokens"].ddl, vec!["CREATE TABLE"]);
+        assert_eq!(stats.tables["access_tokens"].rows, 0);
+    }
+
+    #[test]
+    fn big(){
+        let stats = stats("../resources/test_db/big.sql");
+        let users = &stats.tables["user_details"];
+        assert_eq!(users.inserts, 33);
+        assert!(users.rows > 19_000);
+        assert!(stats.json().contains("\"user_details\""));
+    }
+
+    #[test]
+    fn other_dialects(){
+        // ALTER TABLE ONLY of pg_dump counts for the table
+        let stats = stats_as("../resources/test_db/postgres.sql", Dialect::Postgres);
+        assert_eq!(stats.tables["public.users"].ddl, vec!["CREATE TABLE", "ALTER TABLE", "ALTER TABLE", "ALTER TABLE"]);
+        assert!(!stats.tables.contains_key("ONLY"));
+
+        let stats = stats_as("../resources/test_db/mssql.sql", Dialect::Mssql);
+        assert_eq!((stats.tables["dbo.users"].rows, stats.tables["dbo.orders"].rows), (4, 4));
+        let stats = stats_as("../resources/test_db/sqlite.sql", Dialect::Sqlite);
+        assert_eq!((stats.tables["users"].rows, stats.tables["orders"].rows), (4, 4));
+    }
+}
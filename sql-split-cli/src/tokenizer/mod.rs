@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod tokenizer;
 pub mod token;
 pub mod token_err;
\ No newline at end of file
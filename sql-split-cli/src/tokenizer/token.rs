@@ -5,6 +5,8 @@ pub enum Token{
     String(Vec<u8>),
     Keyword(Vec<u8>),
     Comment(Vec<u8>),
+    // /*!40101 ... */ or /*M!100101 ... */ with the version it needs
+    ConditionalComment(Option<u32>, Vec<u8>),
     InlineComment(Vec<u8>),
     Identifier(Vec<u8>),
     // could be /t or /n /r
@@ -22,7 +24,7 @@ impl Token {
     pub fn keyword(&self, string: &str) -> bool {
         match self {
             Token::Keyword(chunk) => {
-                let value = str::from_utf8(&chunk).unwrap();
+                let value = str::from_utf8(chunk).unwrap();
                 value.to_lowercase() == string
             },
             _ => false,
@@ -34,6 +36,7 @@ impl Token {
             Token::String(chunk) => chunk,
             Token::Keyword(chunk) => chunk,
             Token::Comment(chunk) => chunk,
+            Token::ConditionalComment(_, chunk) => chunk,
             Token::InlineComment(chunk) => chunk,
             Token::Identifier(chunk) => chunk,
             Token::Ignore(byte) => vec![byte],
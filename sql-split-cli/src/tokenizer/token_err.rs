@@ -1,5 +1,14 @@
+use std::fmt;
+
 #[derive(Debug)]
 pub struct TokenErr{
-  // line_num: usize,
-  pub text: &'static str
-}
\ No newline at end of file
+  pub text: &'static str,
+  // input offset of the token or statement that is broken
+  pub pos: usize,
+}
+
+impl fmt::Display for TokenErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at byte {})", self.text, self.pos)
+    }
+}
@@ -1,63 +1,83 @@
+use memchr::memchr2_iter;
 use sql_split_reader::Reader;
+use crate::dialect::{Dialect, Syntax};
 use crate::tokenizer::token::Token;
 use crate::tokenizer::token_err::TokenErr;
 
+// how far peek_line and next_line look ahead
+const LOOKAHEAD: usize = 64;
+
 pub struct Tokenizer {
     reader: Reader,
+    // scan strings, comments and values tuples with memchr instead of
+    // walking them byte by byte.
+    fast_scan: bool,
+    syntax: Syntax,
 }
 
 impl Tokenizer {
     pub fn new(reader: Reader) -> Self {
-        Self {reader}
+        Self {
+            reader,
+            fast_scan: true,
+            syntax: Dialect::Mysql.syntax(),
+        }
     }
 
-    fn read_till(&mut self, item: u8) -> Result<Vec<u8>, TokenErr> {
+    pub fn set_fast_scan(&mut self, enabled: bool) {
+        self.fast_scan = enabled;
+    }
+
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.syntax = dialect.syntax();
+    }
+
+    pub fn fast_scan(&self) -> bool {
+        self.fast_scan
+    }
+
+    pub fn syntax(&self) -> Syntax {
+        self.syntax
+    }
+
+    // input offset of the next byte to be tokenized
+    pub fn position(&self) -> usize {
+        self.reader.position()
+    }
+
+    // reads up to and including `item`. the flag is false when the file
+    // ended first.
+    fn read_till(&mut self, item: u8) -> (Vec<u8>, bool) {
         let mut collection = vec![];
 
-        loop {
-            let byte = self.reader.get();
-            match byte {
+        if self.fast_scan {
+            return match self.reader.read_until_any(&[item], &mut collection) {
                 Some(value) => {
+                    self.reader.increment_index();
                     collection.push(value);
-                    if value == item {
-                        break;
-                    }
+                    (collection, true)
                 },
-                None => {
-                    return Err(TokenErr{
-                        text: "Unexpected end of the file."
-                    })
-                }
+                None => (collection, false),
+            }
+        }
+
+        while let Some(value) = self.reader.get() {
+            collection.push(value);
+            if value == item {
+                return (collection, true)
             }
         }
-        
-        Ok(collection)
+
+        (collection, false)
     }
 
-    fn keyword(&mut self) -> Result<Vec<u8>, TokenErr> {
+    fn keyword(&mut self) -> Vec<u8> {
         let mut collection = vec![];
-        loop {
-            let byte = self.reader.peek();
-            match byte {
-                Some(item)  => {
-                    match item {
-                        b'a'..=b'z' |
-                        b'A'..=b'Z' => {
-                            self.reader.increment_index();
-                            collection.push(item);
-                        },
-                        _ => break,
-                    }
-                },
-                None => {
-                    return Err(TokenErr{
-                        text:"While parsing keyword."
-                    })
-                }
-            }
+        while let Some(item @ (b'a'..=b'z' | b'A'..=b'Z')) = self.reader.peek() {
+            self.reader.increment_index();
+            collection.push(item);
         }
-        
-        Ok(collection)
+        collection
     }
 
 
@@ -70,51 +90,300 @@ impl Tokenizer {
         Token::String(collection)
     }
 
-    fn read_string(&mut self, closing: u8) -> Result<Token, TokenErr> {
+    fn read_string(&mut self, closing: u8, backslash_escapes: bool) -> Result<Token, TokenErr> {
+        let start = self.reader.position();
+        if self.fast_scan {
+            return self.scan_string(closing, backslash_escapes)
+        }
+
         let mut collection = vec![];
-        let mut last_byte = self.reader.get().unwrap();
-        collection.push(last_byte);
+        collection.push(self.reader.get().unwrap());
+        let mut escaped = false;
 
         loop {
             let byte = self.reader.get();
             if let Some(item) = byte {
                 collection.push(item);
-                if item == closing && last_byte != b'\\' {
+                if escaped {
+                    escaped = false;
+                }else if item == b'\\' && backslash_escapes {
+                    escaped = true;
+                }else if item == closing {
                     break;
                 }
-                last_byte = item;
             }else{
                 return Err(TokenErr{
-                    text: "Unclosed string."
+                    text: "Unclosed string.",
+                    pos: start,
                 })
             }
         }
         Ok(Token::String(collection))
     }
 
+    // same as read_string but jumps from one quote or backslash
+    // to the next instead of looking at every byte.
+    fn scan_string(&mut self, closing: u8, backslash_escapes: bool) -> Result<Token, TokenErr> {
+        let start = self.reader.position();
+        let mut collection = vec![];
+        collection.push(self.reader.get().unwrap());
+        let stops = if backslash_escapes { vec![closing, b'\\'] } else { vec![closing] };
+
+        loop {
+            match self.reader.read_until_any(&stops, &mut collection) {
+                Some(b'\\') => {
+                    self.reader.increment_index();
+                    collection.push(b'\\');
+                    // escaped byte is taken as is
+                    match self.reader.get() {
+                        Some(item) => collection.push(item),
+                        None => break,
+                    }
+                },
+                Some(item) => {
+                    self.reader.increment_index();
+                    collection.push(item);
+                    return Ok(Token::String(collection))
+                },
+                None => break,
+            }
+        }
+
+        Err(TokenErr{
+            text: "Unclosed string.",
+            pos: start,
+        })
+    }
+
+    // fast path for the body of a values tuple. expects the opening `(`
+    // to be consumed already and returns everything up to and including
+    // the matching `)`. strings are skipped as a whole so parenthesis
+    // inside them do not count.
+    pub fn tuple_body(&mut self) -> Result<Vec<u8>, TokenErr> {
+        let start = self.reader.position().saturating_sub(1);
+        let mut collection = vec![];
+        let mut depth = 1;
+        loop {
+            let skipped = collection.len();
+            let found = self.reader.read_until_any(b"'\")", &mut collection);
+            // the bytes jumped over can still open a nested `(` e.g.
+            // POINT(1, 2) or end the statement before the tuple is closed
+            for index in memchr2_iter(b'(', b';', &collection[skipped..]) {
+                if collection[skipped + index] == b';' {
+                    return Err(TokenErr{
+                        text: "Unbalanced parenthesis in values tuple.",
+                        pos: start,
+                    })
+                }
+                depth += 1;
+            }
+
+            match found {
+                Some(b')') => {
+                    self.reader.increment_index();
+                    collection.push(b')');
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(collection)
+                    }
+                },
+                Some(closing) => {
+                    let escapes = self.backslash_escapes(&collection);
+                    collection.extend(self.scan_string(closing, escapes)?.value());
+                },
+                None => {
+                    return Err(TokenErr{
+                        text: "Unclosed values tuple.",
+                        pos: start,
+                    })
+                }
+            }
+        }
+    }
+
+    // escaping of a string that starts right after `before`, E'..' in
+    // PostgreSQL takes backslash escapes
+    fn backslash_escapes(&self, before: &[u8]) -> bool {
+        match before {
+            [.., prefix, b'E' | b'e'] if self.syntax.escape_strings => !prefix.is_ascii_alphanumeric() && *prefix != b'_',
+            [b'E' | b'e'] if self.syntax.escape_strings => true,
+            _ => self.syntax.backslash_escapes,
+        }
+    }
+
+    // the closing quote when `byte` opens a quoted identifier
+    fn identifier_quote(&self, byte: u8) -> Option<u8> {
+        self.syntax.identifier_quotes.iter()
+            .find(|(opening, _)| *opening == byte)
+            .map(|(_, closing)| *closing)
+    }
+
+    // prefixes of a quoted string: X'CAFE' blob, N'text' unicode and
+    // E'text' escape strings
+    fn string_prefix(&self, byte: u8) -> bool {
+        match byte {
+            b'x' | b'X' => true,
+            b'n' | b'N' => self.syntax.national_strings,
+            b'e' | b'E' => self.syntax.escape_strings,
+            _ => false,
+        }
+    }
+
+    // $$text$$ or $tag$text$tag$. a `$` that does not open a tag, such
+    // as the $1 of a parameter, is left alone.
+    fn dollar_string(&mut self, start: usize) -> Result<Option<Token>, TokenErr> {
+        let ahead = self.reader.peek_ahead(LOOKAHEAD);
+        let tag_len = ahead[1..].iter()
+            .position(|byte| !(byte.is_ascii_alphanumeric() || *byte == b'_'))
+            .filter(|len| ahead.get(len + 1) == Some(&b'$') && !ahead.get(1).is_some_and(u8::is_ascii_digit));
+        let tag = match tag_len {
+            Some(len) => ahead[..len + 2].to_vec(),
+            None => return self.singular(Token::Ignore(b'$')),
+        };
+
+        let mut collection = self.take(tag.len());
+        loop {
+            if self.reader.read_until_any(b"$", &mut collection).is_none() {
+                return Err(TokenErr{
+                    text: "Unclosed string.",
+                    pos: start,
+                })
+            }
+            if self.reader.peek_ahead(tag.len()) == tag {
+                collection.extend(self.take(tag.len()));
+                return Ok(Some(Token::String(collection)))
+            }
+            self.reader.increment_index();
+            collection.push(b'$');
+        }
+    }
+
+    // the data lines that follow COPY ... FROM stdin; up to and including
+    // the `\.` that ends them
+    pub fn copy_data(&mut self) -> Result<Vec<u8>, TokenErr> {
+        let start = self.reader.position();
+        let (mut collection, _) = self.read_till(b'\n');
+        loop {
+            if self.peek_line() == b"\\." {
+                collection.extend(self.take(2));
+                return Ok(collection)
+            }
+
+            let (line, closed) = self.read_till(b'\n');
+            collection.extend(line);
+            if !closed {
+                return Err(TokenErr{
+                    text: "Unclosed COPY data.",
+                    pos: start,
+                })
+            }
+        }
+    }
+
+    // moves to the first line after `from` that looks like the start of a
+    // statement: a keyword or a comment right at the start of the line.
+    // used to carry on after a syntax error.
+    pub fn skip_to_statement(&mut self, from: usize) {
+        self.reader.seek(from);
+        while self.reader.skip_until_any(b"\n").is_some() {
+            self.reader.increment_index();
+            match (self.reader.peek(), self.reader.peek_next()) {
+                (Some(b'a'..=b'z' | b'A'..=b'Z'), _) |
+                (Some(b'-'), Some(b'-')) |
+                (Some(b'/'), Some(b'*')) => return,
+                _ => {},
+            }
+        }
+    }
+
+    // the rest of the current line, nothing is consumed. cut short after
+    // LOOKAHEAD bytes.
+    pub fn peek_line(&mut self) -> Vec<u8> {
+        let mut line = self.reader.peek_ahead(LOOKAHEAD);
+        if let Some(end) = line.iter().position(|byte| matches!(byte, b'\r' | b'\n')) {
+            line.truncate(end);
+        }
+        line
+    }
+
+    // the start of the next line that is not empty when the line ends
+    // right here, nothing is consumed
+    pub fn next_line(&mut self) -> Option<Vec<u8>> {
+        if !matches!(self.reader.peek(), Some(b'\r' | b'\n')) {
+            return None
+        }
+
+        let ahead = self.reader.peek_ahead(LOOKAHEAD);
+        let start = ahead.iter().position(|byte| !matches!(byte, b'\r' | b'\n')).unwrap_or(ahead.len());
+        let line = &ahead[start..];
+        let end = line.iter().position(|byte| matches!(byte, b'\r' | b'\n')).unwrap_or(line.len());
+        Some(line[..end].to_vec())
+    }
+
+    // the keyword just read is only the start of a name, e.g. begin_date
+    pub fn word_continues(&mut self) -> bool {
+        self.reader.peek().is_some_and(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$')
+    }
+
+    // consumes the rest of the current line, without its line feed
+    pub fn read_line(&mut self) -> Vec<u8> {
+        let mut collection = vec![];
+        self.reader.read_until_any(b"\r\n", &mut collection);
+        collection
+    }
+
+    // consumes `len` bytes as they are
+    pub fn take(&mut self, len: usize) -> Vec<u8> {
+        let mut collection = vec![];
+        while collection.len() < len {
+            match self.reader.get() {
+                Some(byte) => collection.push(byte),
+                None => break,
+            }
+        }
+        collection
+    }
+
     fn singular(&mut self, token: Token) -> Result<Option<Token>, TokenErr> {
         self.reader.increment_index();
         Ok(Some(token))
     }
     
     pub fn token(&mut self) -> Result<Option<Token>, TokenErr> {
+        let start = self.reader.position();
         match self.reader.peek() {
+            Some(byte) if self.identifier_quote(byte).is_some() => {
+                self.identifier(self.identifier_quote(byte).unwrap(), start)
+            },
             Some(closing @ b'"') |
             Some(closing @ b'\'') => {
-                Ok(Some(self.read_string(closing)?))
+                Ok(Some(self.read_string(closing, self.syntax.backslash_escapes)?))
+            },
+            Some(byte) if self.string_prefix(byte) && self.reader.peek_next() == Some(b'\'') => {
+                self.reader.increment_index();
+                let mut string = vec![byte];
+                string.extend(self.read_string(b'\'', self.backslash_escapes(&[byte]))?.value());
+                Ok(Some(Token::String(string)))
             },
+            Some(b'$') if self.syntax.dollar_quotes => self.dollar_string(start),
             Some(byte @ b'/') => {
                 if self.reader.peek_next() == Some(b'*') {
-                    self.comment()
+                    self.comment(start)
                 }else{
                     self.reader.increment_index();
                     Ok(Some(Token::Ignore(byte)))
                 }
             },
+            Some(b'#') if self.syntax.hash_comments => {
+                let (comment, _) = self.read_till(b'\n');
+                Ok(Some(Token::InlineComment(comment)))
+            },
             Some(b'0'..=b'9') => Ok(Some(self.number())),
             Some(byte @ b'-') => {
                 if self.reader.peek_next() == Some(b'-') {
-                    Ok(Some(Token::InlineComment(self.read_till(b'\n')?)))
+                    // the last line of a file may have no line feed
+                    let (comment, _) = self.read_till(b'\n');
+                    Ok(Some(Token::InlineComment(comment)))
                 }else{
                     self.reader.increment_index();
                     Ok(Some(Token::Ignore(byte)))
@@ -122,14 +391,7 @@ impl Tokenizer {
             },
             Some(b'a'..=b'z') | 
             Some(b'A'..=b'Z') => {
-                // let keyword = ;
-                Ok(Some(Token::Keyword(self.keyword()?)))
-            },
-            Some(byte @ b'`') => {
-                self.reader.increment_index(); // skip `
-                let mut identifier = vec![byte];
-                identifier.extend(self.read_till(b'`')?);
-                Ok(Some(Token::Identifier(identifier)))
+                Ok(Some(Token::Keyword(self.keyword())))
             },
             Some(b'.') => self.singular(Token::Dot),
             Some(b'(') => self.singular(Token::LP),
@@ -145,22 +407,61 @@ impl Tokenizer {
         }
     }
 
-    fn comment(&mut self) -> Result<Option<Token>, TokenErr> {
+    // `name`, "name" or [name], a doubled quote ends up as two identifiers
+    fn identifier(&mut self, closing: u8, start: usize) -> Result<Option<Token>, TokenErr> {
+        let mut identifier = vec![self.reader.get().unwrap()];
+        let (name, closed) = self.read_till(closing);
+        if !closed {
+            return Err(TokenErr{
+                text: "Unclosed identifier.",
+                pos: start,
+            })
+        }
+        identifier.extend(name);
+        Ok(Some(Token::Identifier(identifier)))
+    }
+
+    fn comment(&mut self, start: usize) -> Result<Option<Token>, TokenErr> {
         let mut collection = vec![];
+        // the opening /* counts as the first level
+        let mut depth = 0;
         loop {
+            if self.fast_scan && depth > 0 {
+                // only `*` ends the comment and `/` starts a nested one
+                let stops: &[u8] = if self.syntax.nested_comments { b"*/" } else { b"*" };
+                self.reader.read_until_any(stops, &mut collection);
+            }
             let cr = self.reader.get();
             // eof
             if cr.is_none() {
                 return Err(TokenErr{
-                    text: "Incomplete multi-line comment."
+                    text: "Incomplete multi-line comment.",
+                    pos: start,
                 });
             }
             
             collection.push(cr.unwrap());
-            if cr == Some(b'*') && self.reader.peek() == Some(b'/') {
+            if cr == Some(b'/') && self.reader.peek() == Some(b'*') && (depth == 0 || self.syntax.nested_comments) {
+                let get_peeked = self.reader.get();
+                collection.push(get_peeked.unwrap());
+                depth += 1;
+            }else if cr == Some(b'*') && self.reader.peek() == Some(b'/') {
                 let get_peeked = self.reader.get();
                 collection.push(get_peeked.unwrap());
-                break
+                depth -= 1;
+                if depth == 0 {
+                    break
+                }
+            }
+        }
+
+        if self.syntax.conditional_comments {
+            // MariaDB only runs /*M! ones
+            let version = collection.strip_prefix(b"/*!").or_else(|| collection.strip_prefix(b"/*M!"));
+            if let Some(version) = version {
+                let digits = version.iter().take_while(|byte| byte.is_ascii_digit()).count();
+                let version = std::str::from_utf8(&version[..digits]).ok().and_then(|digits| digits.parse().ok());
+                return Ok(Some(Token::ConditionalComment(version, collection)))
             }
         }
         Ok(Some(Token::Comment(collection)))
@@ -0,0 +1,628 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::parser::parser::TokenStream;
+use crate::parser::statement::{definition_words, first_keyword, list, list_at, table_name, StatementKind};
+use crate::parser::values::string;
+
+// database the output chunks are written for, the input is read as MySQL
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Translation {
+    Postgres,
+}
+
+// something the translator dropped or copied as it was
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Untranslated {
+    // input offset of the statement it was first seen in
+    pub pos: usize,
+    pub text: String,
+    pub count: usize,
+}
+
+// table options that mean nothing outside of MySQL
+const TABLE_OPTIONS: [&str; 14] = [
+    "ENGINE", "AUTO_INCREMENT", "CHARSET", "COLLATE", "ROW_FORMAT", "KEY_BLOCK_SIZE", "PACK_KEYS",
+    "CHECKSUM", "DELAY_KEY_WRITE", "AVG_ROW_LENGTH", "MAX_ROWS", "MIN_ROWS", "STATS_PERSISTENT",
+    "STATS_AUTO_RECALC",
+];
+
+// rewrites the statements of a MySQL dump so they load into another
+// database. identifiers and strings are rewritten everywhere, CREATE
+// TABLE is rebuilt and MySQL only statements are dropped.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct Translator {
+    untranslated: Vec<Untranslated>,
+    seen: HashMap<String, usize>,
+    // input offset of the item being translated
+    pos: usize,
+    // the last item was dropped, the `;` and line feed after it go too
+    dropped: bool,
+    // AUTO_INCREMENT column of each table, by table name
+    identity: HashMap<String, String>,
+    // tables with an identity column that got rows since the last setval
+    filled: Vec<String>,
+}
+
+fn is_word(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+}
+
+// `db.users` as "db"."users"
+fn ident(name: &str) -> String {
+    name.split('.').map(|part| format!("\"{}\"", part.replace('"', "\"\""))).collect::<Vec<String>>().join(".")
+}
+
+fn hex(value: &[u8]) -> Vec<u8> {
+    let mut out = b"'\\x".to_vec();
+    for byte in value {
+        out.extend(format!("{:02x}", byte).as_bytes());
+    }
+    out.push(b'\'');
+    out
+}
+
+// what MySQL runs of a /*!50003 ... */ or MariaDB /*M!100100 ... */
+// comment, None for any other comment
+fn conditional(comment: &[u8]) -> Option<&[u8]> {
+    let body = comment.strip_prefix(b"/*!").or_else(|| comment.strip_prefix(b"/*M!"))?;
+    let body = body.strip_suffix(b"*/").unwrap_or(body);
+    let version = body.iter().take_while(|byte| byte.is_ascii_digit()).count();
+    let body = body[version..].trim_ascii();
+    // `/*M!999999\- enable the sandbox mode */` is for the mariadb client
+    if body.starts_with(b"\\-") {
+        return Some(&[])
+    }
+    Some(body)
+}
+
+// ALTER TABLE ... DISABLE KEYS or ENABLE KEYS
+fn keys(statement: &[u8]) -> bool {
+    let statement = String::from_utf8_lossy(statement).to_uppercase();
+    let words: Vec<&str> = statement.trim_end_matches([';', ' ', '\n']).split_ascii_whitespace().collect();
+    matches!(words.as_slice(), [.., "DISABLE" | "ENABLE", "KEYS"])
+}
+
+fn upper(word: Option<&&[u8]>) -> String {
+    word.map(|word| String::from_utf8_lossy(word).to_uppercase()).unwrap_or_default()
+}
+
+impl Translator {
+    // what could not be translated, once per kind of problem
+    pub fn untranslated(&self) -> &[Untranslated] {
+        &self.untranslated
+    }
+
+    fn report(&mut self, text: String) {
+        match self.seen.get(&text) {
+            Some(index) => self.untranslated[*index].count += 1,
+            None => {
+                self.seen.insert(text.clone(), self.untranslated.len());
+                self.untranslated.push(Untranslated { pos: self.pos, text, count: 1 });
+            },
+        }
+    }
+
+    // standard string, '' for a quote. postgres text can not hold NUL.
+    fn string(&mut self, value: &[u8], out: &mut Vec<u8>) {
+        out.push(b'\'');
+        for byte in value {
+            match byte {
+                0 => self.report(String::from("NUL byte dropped from a string")),
+                b'\'' => out.extend(b"''"),
+                _ => out.push(*byte),
+            }
+        }
+        out.push(b'\'');
+    }
+
+    // rewrites backtick identifiers, MySQL strings, hex literals and
+    // unwraps /*! ... */ conditional comments
+    fn lex(&mut self, tokens: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(tokens.len());
+        let mut index = 0;
+        while index < tokens.len() {
+            let byte = tokens[index];
+            let rest = &tokens[index..];
+            let word_start = index == 0 || !is_word(tokens[index - 1]);
+            match byte {
+                b'\'' | b'"' => match string(tokens, index, true) {
+                    Ok((value, end)) => {
+                        self.string(&value, &mut out);
+                        index = end;
+                    },
+                    Err(_) => {
+                        out.extend(rest);
+                        break;
+                    },
+                },
+                b'`' => {
+                    let mut end = index + 1;
+                    let mut name = vec![];
+                    while end < tokens.len() {
+                        match tokens[end] {
+                            b'`' if tokens.get(end + 1) == Some(&b'`') => {
+                                name.push(b'`');
+                                end += 1;
+                            },
+                            b'`' => break,
+                            b'"' => name.extend(b"\"\""),
+                            byte => name.push(byte),
+                        }
+                        end += 1;
+                    }
+                    out.push(b'"');
+                    out.extend(name);
+                    out.push(b'"');
+                    index = end + 1;
+                },
+                b'/' if rest.starts_with(b"/*") => {
+                    let end = rest[2..].windows(2).position(|end| end == b"*/").map_or(rest.len(), |end| end + 4);
+                    match conditional(&rest[..end]) {
+                        Some(body) => out.extend(self.lex(body)),
+                        None => out.extend(&rest[..end]),
+                    }
+                    index += end;
+                },
+                b'-' if rest.starts_with(b"--") => {
+                    let end = rest.iter().position(|byte| *byte == b'\n').map_or(rest.len(), |end| end + 1);
+                    out.extend(&rest[..end]);
+                    index += end;
+                },
+                // 0xCAFE and X'CAFE' are bytea in hex format
+                b'0' if word_start && matches!(rest.get(1), Some(b'x' | b'X')) => {
+                    let len = rest[2..].iter().take_while(|byte| byte.is_ascii_hexdigit()).count();
+                    if len > 0 && !rest.get(2 + len).is_some_and(|byte| is_word(*byte)) {
+                        out.extend(b"'\\x");
+                        out.extend(&rest[2..2 + len]);
+                        out.push(b'\'');
+                        index += 2 + len;
+                    }else{
+                        out.push(byte);
+                        index += 1;
+                    }
+                },
+                b'x' | b'X' if word_start && rest.get(1) == Some(&b'\'') => {
+                    let len = rest[2..].iter().take_while(|byte| byte.is_ascii_hexdigit()).count();
+                    if rest.get(2 + len) == Some(&b'\'') {
+                        out.extend(b"'\\x");
+                        out.extend(&rest[2..2 + len]);
+                        out.push(b'\'');
+                        // past the closing quote
+                        index += 3 + len;
+                    }else{
+                        out.push(byte);
+                        index += 1;
+                    }
+                },
+                // _binary '...' holds bytes, not text
+                b'_' if word_start && rest.len() > 7 && rest[..7].eq_ignore_ascii_case(b"_binary") && !is_word(rest[7]) => {
+                    let start = index + 7 + rest[7..].iter().take_while(|byte| byte.is_ascii_whitespace()).count();
+                    match string(tokens, start, true) {
+                        Ok((value, end)) if matches!(tokens[start], b'\'' | b'"') => {
+                            out.extend(hex(&value));
+                            index = end;
+                        },
+                        _ => {
+                            index = start;
+                        },
+                    }
+                },
+                _ => {
+                    out.push(byte);
+                    index += 1;
+                },
+            }
+        }
+        out
+    }
+
+    // index columns, prefix lengths such as `name`(10) are dropped
+    fn key_columns(&mut self, columns: &[u8]) -> String {
+        let columns: Vec<String> = list(columns).iter().map(|column| {
+            let mut column = *column;
+            if column.ends_with(b")") {
+                if let Some(open) = column.iter().position(|byte| *byte == b'(') {
+                    self.report(String::from("index prefix length dropped"));
+                    column = column[..open].trim_ascii();
+                }
+            }
+            String::from_utf8_lossy(&self.lex(column)).into_owned()
+        }).collect();
+        format!("({})", columns.join(", "))
+    }
+
+    // MySQL column type in postgres. display widths are dropped and
+    // unsigned types get the next bigger type.
+    fn column_type(&mut self, column: &str, mysql: &[u8], unsigned: bool) -> String {
+        let open = mysql.iter().position(|byte| *byte == b'(').unwrap_or(mysql.len());
+        let base = String::from_utf8_lossy(&mysql[..open]).to_lowercase();
+        let args = String::from_utf8_lossy(&self.lex(&mysql[open..])).into_owned();
+        match base.as_str() {
+            "tinyint" | "bool" | "boolean" => String::from("smallint"),
+            "smallint" if unsigned => String::from("integer"),
+            "smallint" => String::from("smallint"),
+            "mediumint" => String::from("integer"),
+            "int" | "integer" if unsigned => String::from("bigint"),
+            "int" | "integer" => String::from("integer"),
+            "bigint" if unsigned => String::from("numeric(20)"),
+            "bigint" | "serial" => String::from("bigint"),
+            "float" => String::from("real"),
+            "double" | "real" => String::from("double precision"),
+            "decimal" | "dec" | "numeric" | "fixed" => format!("numeric{}", args),
+            "datetime" | "timestamp" => format!("timestamp{}", args),
+            "time" => format!("time{}", args),
+            "date" => String::from("date"),
+            "year" => String::from("smallint"),
+            "char" | "varchar" => format!("{}{}", base, args),
+            "tinytext" | "text" | "mediumtext" | "longtext" => String::from("text"),
+            "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => String::from("bytea"),
+            "json" => String::from("json"),
+            "bit" => format!("bit{}", args),
+            // the allowed values become a check
+            "enum" => format!("text CHECK ({} IN {})", column, args),
+            _ => {
+                self.report(format!("column type {} copied as it is", base.to_uppercase()));
+                format!("{}{}", base, args)
+            },
+        }
+    }
+
+    // a column definition. COMMENT becomes a COMMENT ON COLUMN statement
+    // in `after`.
+    fn column(&mut self, table: &str, words: &[&[u8]], after: &mut Vec<String>) -> String {
+        let name = String::from_utf8_lossy(&self.lex(words[0])).into_owned();
+        let unsigned = words.iter().skip(2).any(|word| word.eq_ignore_ascii_case(b"unsigned"));
+        let mut parts = vec![name.clone()];
+        if let Some(mysql) = words.get(1) {
+            parts.push(self.column_type(&name, mysql, unsigned));
+        }
+
+        let mut index = 2;
+        while index < words.len() {
+            let word = upper(words.get(index));
+            match word.as_str() {
+                "UNSIGNED" | "SIGNED" | "ZEROFILL" => {},
+                "CHARSET" | "COLLATE" => index += 1,
+                "CHARACTER" => index += 2,
+                "AUTO_INCREMENT" => parts.push(String::from("GENERATED BY DEFAULT AS IDENTITY")),
+                "COMMENT" => {
+                    index += 1;
+                    if let Some(comment) = words.get(index) {
+                        let comment = String::from_utf8_lossy(&self.lex(comment)).into_owned();
+                        after.push(format!("COMMENT ON COLUMN {}.{} IS {};", table, name, comment));
+                    }
+                },
+                "ON" if upper(words.get(index + 1)) == "UPDATE" => {
+                    self.report(String::from("ON UPDATE of a column dropped"));
+                    index += 2;
+                },
+                _ => parts.push(String::from_utf8_lossy(&self.lex(words[index])).into_owned()),
+            }
+            index += 1;
+        }
+        parts.join(" ")
+    }
+
+    // PRIMARY KEY stays, UNIQUE KEY becomes a constraint and KEY a CREATE
+    // INDEX statement in `after`
+    fn key(&mut self, table: &str, words: &[&[u8]], after: &mut Vec<String>) -> Option<String> {
+        let kind = upper(words.first());
+        // KEY `name` (`a`, `b`) USING BTREE
+        let columns = words.iter().position(|word| word.starts_with(b"("))?;
+        let name = words[..columns].iter().rev()
+            .find(|word| !matches!(upper(Some(word)).as_str(), "KEY" | "INDEX" | "UNIQUE" | "PRIMARY"))
+            .map(|name| String::from_utf8_lossy(&self.lex(name)).into_owned());
+        let key_columns = self.key_columns(words[columns]);
+
+        match kind.as_str() {
+            "PRIMARY" => Some(format!("PRIMARY KEY {}", key_columns)),
+            "UNIQUE" => Some(match name {
+                Some(name) => format!("CONSTRAINT {} UNIQUE {}", name, key_columns),
+                None => format!("UNIQUE {}", key_columns),
+            }),
+            "KEY" | "INDEX" => {
+                let name = name.map(|name| format!("{} ", name)).unwrap_or_default();
+                after.push(format!("CREATE INDEX {}ON {} {};", name, table, key_columns));
+                None
+            },
+            _ => {
+                self.report(format!("{} index dropped", kind));
+                None
+            },
+        }
+    }
+
+    // the options after the column list are dropped. COMMENT becomes a
+    // COMMENT ON TABLE statement.
+    fn table_options(&mut self, table: &str, options: &[u8], after: &mut Vec<String>) {
+        let options = options.trim_ascii();
+        let mut words = vec![];
+        for word in definition_words(options.strip_suffix(b";").unwrap_or(options)) {
+            // ENGINE=InnoDB, COMMENT='x'
+            let mut word = word;
+            while let Some(equals) = word.iter().position(|byte| *byte == b'=').filter(|_| !word.starts_with(b"'")) {
+                words.push(&word[..equals]);
+                word = &word[equals + 1..];
+            }
+            words.push(word);
+        }
+        let words: Vec<&[u8]> = words.into_iter().filter(|word| !word.is_empty()).collect();
+
+        let mut index = 0;
+        while index < words.len() {
+            let option = upper(words.get(index));
+            match option.as_str() {
+                "DEFAULT" => {},
+                "CHARACTER" => index += 2,
+                "COMMENT" => {
+                    index += 1;
+                    if let Some(comment) = words.get(index) {
+                        let comment = String::from_utf8_lossy(&self.lex(comment)).into_owned();
+                        after.push(format!("COMMENT ON TABLE {} IS {};", table, comment));
+                    }
+                },
+                "PARTITION" => {
+                    self.report(String::from("PARTITION BY dropped"));
+                    break;
+                },
+                _ if TABLE_OPTIONS.contains(&option.as_str()) => index += 1,
+                _ => {
+                    self.report(format!("table option {} dropped", option));
+                    index += 1;
+                },
+            }
+            index += 1;
+        }
+    }
+
+    fn create_table(&mut self, tokens: &[u8]) -> Vec<u8> {
+        let (definitions, open, close) = match list_at(tokens) {
+            Some(list) => list,
+            None => {
+                // CREATE TABLE ... LIKE or AS SELECT
+                self.report(String::from("CREATE TABLE without columns copied as it is"));
+                return self.lex(tokens)
+            },
+        };
+        let table = ident(&table_name(tokens).unwrap_or_default());
+
+        let mut after = vec![];
+        let mut lines = vec![];
+        for definition in &definitions {
+            let words = definition_words(definition);
+            let line = match upper(words.first()).as_str() {
+                _ if words.is_empty() => None,
+                _ if matches!(definition[0], b'`' | b'"') => Some(self.column(&table, &words, &mut after)),
+                "PRIMARY" | "UNIQUE" | "KEY" | "INDEX" | "FULLTEXT" | "SPATIAL" => self.key(&table, &words, &mut after),
+                "CONSTRAINT" | "FOREIGN" | "CHECK" => Some(String::from_utf8_lossy(&self.lex(definition)).into_owned()),
+                _ => Some(self.column(&table, &words, &mut after)),
+            };
+            lines.extend(line);
+        }
+        self.table_options(&table, &tokens[close + 1..], &mut after);
+        let auto_increment = definitions.iter().map(|definition| definition_words(definition))
+            .find(|words| words.iter().skip(2).any(|word| word.eq_ignore_ascii_case(b"AUTO_INCREMENT")));
+        if let (Some(name), Some(words)) = (table_name(tokens), auto_increment) {
+            let column = String::from_utf8_lossy(words[0]).trim_matches(['`', '"']).replace("``", "`");
+            self.identity.insert(name, column);
+        }
+
+        let mut out = self.lex(&tokens[..open]);
+        out.extend(b"(\n  ");
+        out.extend(lines.join(",\n  ").as_bytes());
+        out.extend(b"\n);");
+        for statement in after {
+            out.push(b'\n');
+            out.extend(statement.as_bytes());
+        }
+        out
+    }
+
+    fn statement(&mut self, tokens: &[u8], kind: &StatementKind) -> Option<Vec<u8>> {
+        let lexed = self.lex(tokens);
+        // `;` after a statement that was dropped
+        if lexed.iter().all(|byte| byte.is_ascii_whitespace() || *byte == b';') {
+            return None
+        }
+
+        match first_keyword(&lexed).as_deref() {
+            Some("SET") => {
+                self.report(String::from("SET statement dropped"));
+                None
+            },
+            Some("LOCK") | Some("UNLOCK") => None,
+            // /*!40000 ALTER TABLE `users` DISABLE KEYS */;
+            Some("ALTER") if keys(&lexed) => None,
+            Some("CREATE") if matches!(kind, StatementKind::CreateTable(_)) => Some(self.create_table(tokens)),
+            Some("DROP") | Some("TRUNCATE") => Some(lexed),
+            Some(keyword) => {
+                self.report(format!("{} statement copied as it is", keyword));
+                Some(lexed)
+            },
+            None => Some(lexed),
+        }
+    }
+
+    // an identity column does not move past the ids the INSERTs wrote, the
+    // sequence is set to the highest one in place of the next statement
+    // that is dropped, like UNLOCK TABLES, or else before the next one
+    pub fn sequences(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.filled).into_iter().map(|table| {
+            let column = &self.identity[&table];
+            format!(
+                "SELECT setval(pg_get_serial_sequence('{}', '{}'), max({})) FROM {};",
+                ident(&table).replace('\'', "''"), column.replace('\'', "''"), ident(column), ident(&table),
+            )
+        }).collect()
+    }
+
+    // the item translated, None when it was dropped. `pos` is where it
+    // starts in the input.
+    pub fn item(&mut self, item: TokenStream, pos: usize) -> Option<TokenStream> {
+        self.pos = pos;
+        let dropped = std::mem::replace(&mut self.dropped, false);
+        let item = match item {
+            TokenStream::Insert(tokens, insert_stmt) => {
+                if first_keyword(insert_stmt.get(6..).unwrap_or_default()).as_deref() == Some("IGNORE") {
+                    self.report(String::from("INSERT IGNORE copied as it is"));
+                }
+                if let Some(table) = table_name(&insert_stmt).filter(|table| self.identity.contains_key(table)) {
+                    if !self.filled.contains(&table) {
+                        self.filled.push(table);
+                    }
+                }
+                Some(TokenStream::Insert(self.lex(&tokens), self.lex(&insert_stmt)))
+            },
+            TokenStream::ValuesTuple(tokens) => Some(TokenStream::ValuesTuple(self.lex(&tokens))),
+            TokenStream::Block(tokens, kind) => match self.statement(&tokens, &kind) {
+                Some(tokens) => Some(TokenStream::Block(tokens, kind)),
+                None if !self.filled.is_empty() => Some(TokenStream::Block(self.sequences().join("\n").into_bytes(), StatementKind::Other)),
+                None => None,
+            },
+            TokenStream::Comment(tokens) if conditional(&tokens).is_some() => {
+                if conditional(&tokens).is_some_and(|body| !body.is_empty()) {
+                    self.report(String::from("conditional comment outside of a statement dropped"));
+                }
+                None
+            },
+            TokenStream::Comment(tokens) => Some(TokenStream::Comment(tokens)),
+            // the `;` and line feed of a dropped statement
+            TokenStream::SpaceOrLineFeed(tokens) if dropped && tokens == b";" => None,
+            TokenStream::SpaceOrLineFeed(tokens) if dropped && tokens == b"\n" => return None,
+            TokenStream::SpaceOrLineFeed(tokens) => return Some(TokenStream::SpaceOrLineFeed(tokens)),
+        };
+        self.dropped = item.is_none();
+        item
+    }
+}
+
+
+#[cfg(test)]
+mod translate_test{
+    use crate::parser::parser::TokenStream;
+    use crate::splitter::{Splitter, SplitterSettings, SplitterState};
+    use crate::splitter::splitter_test::test_settings;
+
+    use super::{Translation, Translator};
+
+    fn statement(translator: &mut Translator, sql: &str) -> String {
+        match translator.item(TokenStream::block(sql.as_bytes().to_vec()), 0) {
+            Some(TokenStream::Block(tokens, _)) => String::from_utf8(tokens).unwrap(),
+            _ => String::new(),
+        }
+    }
+
+    #[test]
+    fn values(){
+        let mut translator = Translator::default();
+        let tuple = b"(3,'o\\'brien',\"a\\\\b\\n\",0x1F,_binary 'ab',X'cafe',`x`);".to_vec();
+        match translator.item(TokenStream::ValuesTuple(tuple), 0) {
+            Some(TokenStream::ValuesTuple(tokens)) => assert_eq!(
+                String::from_utf8(tokens).unwrap(),
+                "(3,'o''brien','a\\b\n','\\x1F','\\x6162','\\xcafe',\"x\");"
+            ),
+            _ => panic!("expected a values tuple"),
+        }
+        assert!(translator.untranslated().is_empty());
+
+        // not hex, or cut before the closing quote: lexed as a string
+        for (tuple, expected) in [("(X'zz',1);", "(X'zz',1);"), ("(x'ca", "(x'ca")] {
+            match translator.item(TokenStream::ValuesTuple(tuple.as_bytes().to_vec()), 0) {
+                Some(TokenStream::ValuesTuple(tokens)) => assert_eq!(String::from_utf8(tokens).unwrap(), expected),
+                _ => panic!("expected a values tuple"),
+            }
+        }
+    }
+
+    #[test]
+    fn create_table(){
+        let mut translator = Translator::default();
+        let create = statement(&mut translator, "CREATE TABLE `users` (
+  `id` int unsigned NOT NULL AUTO_INCREMENT,
+  `name` varchar(50) CHARACTER SET utf8mb4 COLLATE utf8mb4_bin NOT NULL COMMENT 'login, unique',
+  `kind` enum('a','b') DEFAULT 'a',
+  `price` decimal(10,2) NOT NULL DEFAULT '0.00',
+  `created_at` datetime DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  `data` longblob,
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `users_name` (`name`),
+  KEY `users_kind` (`kind`,`name`(10)) USING BTREE
+) ENGINE=InnoDB AUTO_INCREMENT=4 DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_0900_ai_ci COMMENT='all users';");
+        assert_eq!(create, "CREATE TABLE \"users\" (
+  \"id\" bigint NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+  \"name\" varchar(50) NOT NULL,
+  \"kind\" text CHECK (\"kind\" IN ('a','b')) DEFAULT 'a',
+  \"price\" numeric(10,2) NOT NULL DEFAULT '0.00',
+  \"created_at\" timestamp DEFAULT CURRENT_TIMESTAMP,
+  \"data\" bytea,
+  PRIMARY KEY (\"id\"),
+  CONSTRAINT \"users_name\" UNIQUE (\"name\")
+);
+COMMENT ON COLUMN \"users\".\"name\" IS 'login, unique';
+CREATE INDEX \"users_kind\" ON \"users\" (\"kind\", \"name\");
+COMMENT ON TABLE \"users\" IS 'all users';");
+
+        let reported: Vec<&str> = translator.untranslated().iter().map(|untranslated| untranslated.text.as_str()).collect();
+        assert_eq!(reported, vec!["ON UPDATE of a column dropped", "index prefix length dropped"]);
+    }
+
+    #[test]
+    fn statements(){
+        let mut translator = Translator::default();
+        assert_eq!(statement(&mut translator, "DROP TABLE IF EXISTS `users`;"), "DROP TABLE IF EXISTS \"users\";");
+        assert_eq!(statement(&mut translator, "LOCK TABLES `users` WRITE;"), "");
+        assert_eq!(statement(&mut translator, "SET NAMES utf8mb4;"), "");
+        assert_eq!(statement(&mut translator, "SET time_zone = \"+00:00\";"), "");
+        assert_eq!(statement(&mut translator, "/*!40101 SET NAMES utf8mb4 */;"), "");
+        assert_eq!(statement(&mut translator, "/*!40000 ALTER TABLE `users` DISABLE KEYS */;"), "");
+        assert_eq!(statement(&mut translator, "CREATE VIEW `v` AS SELECT 1;"), "CREATE VIEW \"v\" AS SELECT 1;");
+
+        let reported: Vec<(&str, usize)> = translator.untranslated().iter()
+            .map(|untranslated| (untranslated.text.as_str(), untranslated.count))
+            .collect();
+        assert_eq!(reported, vec![("SET statement dropped", 3), ("CREATE statement copied as it is", 1)]);
+    }
+
+    #[test]
+    fn conditional_comments(){
+        // mysqldump writes triggers inside conditional comments
+        let settings = SplitterSettings {
+            translate: Some(Translation::Postgres),
+            ..test_settings("../resources/test_db/routines.sql")
+        };
+        let mut splitter = Splitter::new(settings);
+        let mut out = vec![];
+        loop {
+            match splitter.process() {
+                SplitterState::Chunk(_, tokens) => out.extend(tokens),
+                SplitterState::Done => break,
+                _ => panic!("expected the dump to parse"),
+            }
+        }
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("DELIMITER ;;
+CREATE DEFINER=\"root\"@\"localhost\" TRIGGER \"orders_total\" BEFORE INSERT ON \"orders\" FOR EACH ROW BEGIN
+  IF NEW.total < 0 THEN
+    SET NEW.total = 0;
+  END IF;
+END;;
+DELIMITER ;
+"));
+        assert!(!out.contains("/*!"));
+
+        // the ids of the INSERTs move the identity column on
+        let setval = "SELECT setval(pg_get_serial_sequence('\"orders\"', 'id'), max(\"id\")) FROM \"orders\";\n";
+        assert!(out.contains(&format!("INSERT INTO \"orders\" VALUES (1,19.99),(2,5.00);\n{}DELIMITER ;;\n", setval)));
+        assert!(out.ends_with(&format!("INSERT INTO \"orders\" VALUES (3,120.50);\n{}", setval)));
+
+        let reported: Vec<(&str, usize)> = splitter.untranslated().iter()
+            .map(|untranslated| (untranslated.text.as_str(), untranslated.count))
+            .collect();
+        assert_eq!(reported, vec![
+            ("SET statement dropped", 3),
+            ("DELIMITER statement copied as it is", 4),
+            ("CREATE statement copied as it is", 2),
+        ]);
+    }
+}
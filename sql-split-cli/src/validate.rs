@@ -0,0 +1,164 @@
+use crate::parser::parser::{Parser, StreamSource, TokenStream};
+use crate::tokenizer::token_err::TokenErr;
+
+// goes through a whole dump and collects every problem on the way instead
+// of giving up on the first one
+pub struct Validator {
+    problems: Vec<TokenErr>,
+    // last INSERT or values tuple ended with `,` so more rows must follow
+    open_insert: bool,
+    // dump starts with `-- MySQL dump`
+    mysqldump: bool,
+    // last statement was the `-- Dump completed` comment
+    completed: bool,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self {
+            problems: vec![],
+            open_insert: false,
+            mysqldump: false,
+            completed: false,
+        }
+    }
+
+    fn problem(&mut self, text: &'static str, pos: usize) {
+        self.problems.push(TokenErr{ text, pos });
+    }
+
+    // an INSERT left open by its last tuple runs into something else
+    fn close_insert(&mut self, pos: usize) {
+        if std::mem::replace(&mut self.open_insert, false) {
+            self.problem("INSERT without terminator.", pos);
+        }
+    }
+
+    fn check(&mut self, item: TokenStream, start: usize) {
+        match item {
+            TokenStream::Insert(tokens, _) => {
+                self.close_insert(start);
+                self.open_insert = tokens.ends_with(b",");
+                self.completed = false;
+            },
+            TokenStream::ValuesTuple(tokens) => {
+                if !self.open_insert {
+                    self.problem("Values tuple outside of an INSERT statement.", start);
+                }
+                self.open_insert = tokens.ends_with(b",");
+                self.completed = false;
+            },
+            TokenStream::Block(..) => {
+                self.close_insert(start);
+                self.completed = false;
+            },
+            TokenStream::Comment(tokens) => {
+                if start == 0 {
+                    self.mysqldump = tokens.starts_with(b"-- MySQL dump");
+                }
+                self.completed = tokens.starts_with(b"-- Dump completed");
+            },
+            TokenStream::SpaceOrLineFeed(_) => {},
+        }
+    }
+
+    // problems in the order they show up in the input
+    pub fn run(mut self, parser: &mut Parser) -> Vec<TokenErr> {
+        loop {
+            let start = StreamSource::position(parser);
+            match parser.token_stream() {
+                Ok(Some(item)) => self.check(item, start),
+                Ok(None) => break,
+                Err(e) => {
+                    self.problems.push(e);
+                    self.open_insert = false;
+                    parser.recover();
+                },
+            }
+        }
+
+        let end = StreamSource::position(parser);
+        self.close_insert(end);
+        if self.mysqldump && !self.completed {
+            self.problem("Missing `-- Dump completed` footer, the dump may be truncated.", end);
+        }
+
+        self.problems.sort_by_key(|problem| problem.pos);
+        self.problems
+    }
+}
+
+
+#[cfg(test)]
+mod validate_test{
+    use std::fs::{self, File};
+    use std::env;
+    use crate::dialect::Dialect;
+    use crate::parser::parser::Parser;
+
+    use super::Validator;
+
+    fn validate(path: &str) -> Vec<(usize, &'static str)> {
+        validate_as(path, Dialect::Mysql)
+    }
+
+    fn validate_as(path: &str, dialect: Dialect) -> Vec<(usize, &'static str)> {
+        let mut parser = Parser::from_file(File::open(path).unwrap(), true, dialect);
+        Validator::new().run(&mut parser).iter().map(|problem| (problem.pos, problem.text)).collect()
+    }
+
+    // runs the validator over `content` with both scanners
+    fn validate_str(name: &str, content: &str) -> Vec<(usize, &'static str)> {
+        let path = env::temp_dir().join(format!("sql-split-validate-{}.sql", name));
+        fs::write(&path, content).unwrap();
+        let fast = validate(path.to_str().unwrap());
+        let mut parser = Parser::from_file(File::open(&path).unwrap(), false, Dialect::Mysql);
+        let slow: Vec<_> = Validator::new().run(&mut parser).iter().map(|problem| (problem.pos, problem.text)).collect();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(fast, slow);
+        fast
+    }
+
+    #[test]
+    fn valid_dumps(){
+        for path in ["small.sql", "big.sql", "preamble.sql", "mysqldump.sql", "create-table-with-comments.sql"] {
+            assert_eq!(validate(&format!("../resources/test_db/{}", path)), vec![], "{}", path);
+        }
+
+        for (path, dialect) in [("postgres.sql", Dialect::Postgres), ("mssql.sql", Dialect::Mssql), ("sqlite.sql", Dialect::Sqlite)] {
+            let path = format!("../resources/test_db/{}", path);
+            assert_eq!(validate_as(&path, dialect), vec![], "{}", path);
+            // read as MySQL they are not well-formed
+            assert!(!validate(&path).is_empty(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn truncated_mysqldump(){
+        let dump = fs::read_to_string("../resources/test_db/mysqldump.sql").unwrap();
+        let cut = dump.find("(3,'o").unwrap();
+        let problems = validate_str("truncated", &dump[..cut + 5]);
+        assert_eq!(problems, vec![
+            (cut + 3, "Unclosed string."),
+            (cut + 5, "Missing `-- Dump completed` footer, the dump may be truncated."),
+        ]);
+    }
+
+    #[test]
+    fn continues_past_errors(){
+        let dump = "INSERT INTO `a` VALUES (1,'x'),(2,'y';\n\
+            INSERT INTO `b` VALUES (1),\n(2)),\n(3);\n\
+            INSERT INTO `c` VALUES (1,POINT(1,2)),\n(2);\n\
+            INSERT INTO `d` VALUES (1),\n\
+            CREATE TABLE `e` (`id` int);\n\
+            /* not closed\n";
+        let problems = validate_str("errors", dump);
+        let at = |needle: &str| dump.find(needle).unwrap();
+        assert_eq!(problems, vec![
+            (at("(2,'y'"), "Unbalanced parenthesis in values tuple."),
+            (at("(2))") + 3, "Unbalanced parenthesis in values tuple."),
+            (at("CREATE"), "INSERT without terminator."),
+            (at("/*"), "Incomplete multi-line comment."),
+        ]);
+    }
+}
@@ -0,0 +1,252 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use sql_split_reader::Reader;
+
+use crate::dialect::Dialect;
+use crate::merge::open_chunk;
+use crate::parser::parser::{Parser, StreamSource, TokenStream};
+use crate::parser::statement::{row, table_name, StatementKind};
+use crate::splitter::CHUNK_TRANSACTION;
+use crate::tokenizer::tokenizer::Tokenizer;
+use crate::tokenizer::token_err::TokenErr;
+
+// what a dump holds, independent of how it was cut into statements
+#[derive(Debug, PartialEq, Default)]
+pub struct Summary {
+    pub rows: usize,
+    // sum of the (table, row) hashes so the order of rows does not matter
+    pub row_sum: [u64; 4],
+    pub statements: usize,
+    // hash over the non-INSERT statements in order
+    pub statement_hash: [u8; 32],
+}
+
+// builds a Summary while streaming through one or more inputs
+#[derive(Default)]
+struct Hasher {
+    rows: usize,
+    row_sum: [u64; 4],
+    statements: usize,
+    statement_hash: Sha256,
+    table: String,
+    // the next statement was added by --chunk-transactions
+    added: bool,
+}
+
+impl Hasher {
+    fn row(&mut self, tuple: &[u8]) {
+        let mut hasher = Sha256::new();
+        hasher.update(self.table.as_bytes());
+        hasher.update([0]);
+        hasher.update(row(tuple));
+        let digest = hasher.finalize();
+
+        for (lane, bytes) in self.row_sum.iter_mut().zip(digest.chunks(8)) {
+            *lane = lane.wrapping_add(u64::from_le_bytes(bytes.try_into().unwrap()));
+        }
+        self.rows += 1;
+    }
+
+    fn add(&mut self, source: &mut dyn StreamSource) -> Result<(), TokenErr> {
+        while let Some(item) = source.token_stream()? {
+            match item {
+                TokenStream::Insert(tokens, insert_stmt) => {
+                    self.table = table_name(&insert_stmt).unwrap_or_default();
+                    self.row(&tokens[insert_stmt.len().saturating_sub(1)..]);
+                },
+                TokenStream::ValuesTuple(tokens) => self.row(&tokens),
+                TokenStream::Block(_, StatementKind::BeginTransaction | StatementKind::EndTransaction) if self.added => {
+                    self.added = false;
+                },
+                TokenStream::Block(tokens, _) => {
+                    self.added = false;
+                    self.statement_hash.update(tokens.trim_ascii());
+                    self.statement_hash.update([0]);
+                    self.statements += 1;
+                },
+                TokenStream::Comment(tokens) => self.added = tokens == CHUNK_TRANSACTION,
+                TokenStream::SpaceOrLineFeed(_) => {},
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Summary {
+        Summary {
+            rows: self.rows,
+            row_sum: self.row_sum,
+            statements: self.statements,
+            statement_hash: self.statement_hash.finalize().into(),
+        }
+    }
+}
+
+pub fn summarize_input(path: &Path, dialect: Dialect) -> Result<Summary, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut tokenizer = Tokenizer::new(Reader::new(file));
+    tokenizer.set_dialect(dialect);
+    let mut hasher = Hasher::default();
+    hasher.add(&mut Parser::new(tokenizer)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(hasher.finish())
+}
+
+// `preamble` is what --replay-preamble repeated at the top of the chunks
+pub fn summarize_chunks(chunks: &[PathBuf], preamble: &[u8], dialect: Dialect) -> Result<Summary, String> {
+    let mut hasher = Hasher::default();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let preamble = if index > 0 { preamble } else { &[] };
+        let mut parser = open_chunk(chunk, preamble, dialect).map_err(|e| format!("{}: {}", chunk.display(), e))?;
+        hasher.add(&mut parser).map_err(|e| format!("{}: {}", chunk.display(), e))?;
+    }
+    Ok(hasher.finish())
+}
+
+// every difference between the input and the chunks, empty when they hold
+// the same data
+pub fn compare(input: &Summary, chunks: &Summary) -> Vec<String> {
+    let mut mismatches = vec![];
+    if input.rows != chunks.rows {
+        mismatches.push(format!("rows: {} in the input, {} in the chunks", input.rows, chunks.rows));
+    }else if input.row_sum != chunks.row_sum {
+        mismatches.push(String::from("rows: same number of rows but their values differ"));
+    }
+
+    if input.statements != chunks.statements {
+        mismatches.push(format!(
+            "statements: {} in the input, {} in the chunks",
+            input.statements, chunks.statements
+        ));
+    }else if input.statement_hash != chunks.statement_hash {
+        mismatches.push(String::from("statements: same number of statements but they differ"));
+    }
+    mismatches
+}
+
+
+#[cfg(test)]
+mod verify_test{
+    use std::fs;
+    use std::path::Path;
+    use crate::dialect::Dialect;
+    use crate::merge::merge_test::{split, split_with};
+    use crate::merge::open_chunk;
+    use crate::splitter::SplitterSettings;
+    use crate::splitter::splitter_test::test_settings;
+    use crate::stats::Stats;
+
+    use super::{compare, summarize_chunks, summarize_input};
+
+    #[test]
+    fn split_keeps_data(){
+        for (path, write, replay_preamble) in [
+            ("../resources/test_db/big.sql", 10 * 1024, false),
+            ("../resources/test_db/small.sql", 64, false),
+            ("../resources/test_db/preamble.sql", 1, true),
+            ("../resources/test_db/preamble.sql", 100, true),
+            ("../resources/test_db/mysqldump.sql", 150, false),
+            ("../resources/test_db/triggers.sql", 1, false),
+            // every INSERT is bigger than a chunk
+            ("../resources/test_db/oversized.sql", 1024, false),
+        ] {
+            let input = summarize_input(Path::new(path), Dialect::Mysql).unwrap();
+            assert!(input.rows > 0);
+
+            let (chunks, preamble) = split(path, write, replay_preamble, None, &format!("verify-{}", write));
+            let summary = summarize_chunks(&chunks, &preamble, Dialect::Mysql).unwrap();
+            assert_eq!(compare(&input, &summary), Vec::<String>::new(), "{}", path);
+            for chunk in chunks {
+                fs::remove_file(chunk).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn reports_mismatch(){
+        let path = "../resources/test_db/mysqldump.sql";
+        let input = summarize_input(Path::new(path), Dialect::Mysql).unwrap();
+        let (chunks, _) = split(path, 1024 * 1024, false, None, "verify-mismatch");
+        assert_eq!(chunks.len(), 1);
+
+        let dump = fs::read_to_string(&chunks[0]).unwrap();
+        fs::write(&chunks[0], dump.replace("'bob'", "'rob'")).unwrap();
+        let summary = summarize_chunks(&chunks, &[], Dialect::Mysql).unwrap();
+        assert_eq!(compare(&input, &summary), vec!["rows: same number of rows but their values differ"]);
+
+        fs::write(&chunks[0], dump.replace("UNLOCK TABLES;", "")).unwrap();
+        let summary = summarize_chunks(&chunks, &[], Dialect::Mysql).unwrap();
+        assert_eq!(compare(&input, &summary), vec!["statements: 26 in the input, 24 in the chunks"]);
+        fs::remove_file(&chunks[0]).unwrap();
+    }
+
+    #[test]
+    fn rows_per_insert(){
+        for (path, write, rows) in [
+            ("../resources/test_db/big.sql", 50 * 1024, 100),
+            ("../resources/test_db/small.sql", 1024, 2),
+            ("../resources/test_db/mysqldump.sql", 200, 1),
+            ("../resources/test_db/preamble.sql", 1024, 4),
+        ] {
+            let input = summarize_input(Path::new(path), Dialect::Mysql).unwrap();
+            let (chunks, _) = split(path, write, false, Some(rows), &format!("rebatch-{}", rows));
+            assert_eq!(compare(&input, &summarize_chunks(&chunks, &[], Dialect::Mysql).unwrap()), Vec::<String>::new(), "{}", path);
+
+            // every INSERT holds at most `rows` rows
+            let mut inserts = 0;
+            for chunk in &chunks {
+                let stats = Stats::collect(&mut open_chunk(chunk, &[], Dialect::Mysql).unwrap()).unwrap();
+                for table in stats.tables.values() {
+                    assert!(table.rows <= table.inserts * rows, "{}", path);
+                    inserts += table.inserts;
+                }
+            }
+            assert!(inserts >= input.rows.div_ceil(rows), "{}", path);
+            for chunk in chunks {
+                fs::remove_file(chunk).unwrap();
+            }
+        }
+
+        // the two single row INSERTs of `hello` become one
+        let (chunks, _) = split("../resources/test_db/small.sql", 1024 * 1024, false, Some(10), "rebatch-merge");
+        let output = fs::read_to_string(&chunks[0]).unwrap();
+        assert_eq!(output.matches("INSERT INTO `hello`").count(), 1);
+        assert!(output.contains("(1, 34, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1),\n(2, 35, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1);\n"));
+        fs::remove_file(&chunks[0]).unwrap();
+    }
+
+    #[test]
+    fn sqlite_dump(){
+        let path = Path::new("../resources/test_db/sqlite.sql");
+        let input = summarize_input(path, Dialect::Sqlite).unwrap();
+        assert_eq!(input.rows, 9);
+        // read as MySQL the backslash in 'C:\temp\' escapes the quote
+        assert!(summarize_input(path, Dialect::Mysql).is_err());
+
+        // the COMMIT and BEGIN --chunk-transactions adds are left out, the
+        // ones of the dump are not
+        let (chunks, _) = split_with(SplitterSettings {
+            write: 200,
+            dialect: Dialect::Sqlite,
+            chunk_transactions: true,
+            ..test_settings(path.to_str().unwrap())
+        }, "verify-transactions");
+        assert!(chunks.len() > 3);
+        assert_eq!(compare(&input, &summarize_chunks(&chunks, &[], Dialect::Sqlite).unwrap()), Vec::<String>::new());
+
+        let last = chunks.last().unwrap();
+        let dump = fs::read_to_string(last).unwrap();
+        fs::write(last, dump.replace("PRAGMA writable_schema=OFF;\nCOMMIT;", "PRAGMA writable_schema=OFF;")).unwrap();
+        assert_eq!(compare(&input, &summarize_chunks(&chunks, &[], Dialect::Sqlite).unwrap()), vec!["statements: 11 in the input, 10 in the chunks"]);
+        for chunk in chunks {
+            fs::remove_file(chunk).unwrap();
+        }
+    }
+
+    #[test]
+    fn mssql_script(){
+        let path = Path::new("../resources/test_db/mssql.sql");
+        let input = summarize_input(path, Dialect::Mssql).unwrap();
+        assert_eq!(input.rows, 8);
+        assert_eq!(input.statements, 20);
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use sha2::{Digest, Sha256};
+
+use crate::splitter::FileState;
+
+pub fn chunk_name(count: usize) -> String {
+    format!("{:?}.sql", count)
+}
+
+// a chunk that is fully written to disk
+#[derive(Debug, PartialEq, Clone)]
+pub struct WrittenChunk {
+    pub file: String,
+    pub bytes: usize,
+    pub sha256: String,
+}
+
+// writes the chunks handed out by the Splitter into ./1.sql, ./2.sql ...
+pub struct ChunkWriter {
+    // number of the chunk being written
+    count: usize,
+    file: Option<BufWriter<File>>,
+    bytes: usize,
+    hasher: Sha256,
+}
+
+impl ChunkWriter {
+    // `completed` chunks are already on disk, e.g. when resuming.
+    // the next chunk is created from scratch which truncates whatever
+    // a crashed run left behind.
+    pub fn new(completed: usize) -> Self {
+        Self {
+            count: completed,
+            file: None,
+            bytes: 0,
+            hasher: Sha256::new(),
+        }
+    }
+
+    // returns the previous chunk when this write completed it
+    pub fn write(&mut self, file_state: &FileState, tokens: &[u8]) -> io::Result<Option<WrittenChunk>> {
+        let mut completed = None;
+        if *file_state == FileState::New || self.file.is_none() {
+            completed = self.close()?;
+            self.count += 1;
+            self.file = Some(BufWriter::new(File::create(format!("./{}", chunk_name(self.count)))?));
+        }
+
+        self.file.as_mut().unwrap().write_all(tokens)?;
+        self.bytes += tokens.len();
+        self.hasher.update(tokens);
+        Ok(completed)
+    }
+
+    // flushes the current chunk all the way to disk
+    pub fn close(&mut self) -> io::Result<Option<WrittenChunk>> {
+        match self.file.take() {
+            Some(file) => {
+                let file = file.into_inner().map_err(|e| e.into_error())?;
+                file.sync_all()?;
+
+                let digest = std::mem::take(&mut self.hasher).finalize();
+                let chunk = WrittenChunk {
+                    file: chunk_name(self.count),
+                    bytes: self.bytes,
+                    sha256: digest.iter().map(|byte| format!("{:02x}", byte)).collect(),
+                };
+                self.bytes = 0;
+                Ok(Some(chunk))
+            },
+            None => Ok(None),
+        }
+    }
+}
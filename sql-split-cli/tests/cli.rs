@@ -0,0 +1,76 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../resources/test_db").join(name)
+}
+
+// an empty directory to run in, the chunks are written to the working
+// directory
+fn work_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("sql-split-cli-test-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(dir: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sql-split-cli")).current_dir(dir).args(args).output().unwrap()
+}
+
+#[test]
+fn errors_exit_with_1(){
+    let dir = work_dir("errors");
+    let broken = fixture("broken.sql");
+    let output = run(&dir, &["stats", broken.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unbalanced parenthesis"));
+
+    // nothing to resume from
+    let small = fixture("small.sql");
+    let output = run(&dir, &[small.to_str().unwrap(), "--output", "1mb", "--resume"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "no checkpoint found to resume from\n");
+
+    let output = run(&dir, &["stats", small.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(0));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// every file the split left in `dir` with its content
+fn outputs(dir: &PathBuf) -> Vec<(String, Vec<u8>)> {
+    let mut files: Vec<_> = fs::read_dir(dir).unwrap().map(|entry| {
+        let path = entry.unwrap().path();
+        (path.file_name().unwrap().to_string_lossy().into_owned(), fs::read(&path).unwrap())
+    }).collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn threads_match_a_single_thread(){
+    // small segments so every thread gets many of them
+    for (name, size, dialect, segment_size) in [
+        ("big.sql", "100kb", "mysql", "4kb"),
+        ("routines.sql", "1kb", "mysql", "1kb"),
+        ("postgres.sql", "1kb", "postgres", "1kb"),
+    ] {
+        let input = fixture(name);
+        let input = input.to_str().unwrap();
+        let sequential = work_dir("sequential");
+        let output = run(&sequential, &[input, "--output", size, "--dialect", dialect]);
+        assert_eq!(output.status.code(), Some(0), "{}", name);
+
+        let parallel = work_dir("parallel");
+        let output = run(&parallel, &[input, "--output", size, "--dialect", dialect, "--threads", "4", "--segment-size", segment_size]);
+        assert_eq!(output.status.code(), Some(0), "{}", name);
+
+        let files = outputs(&sequential);
+        assert!(files.len() > 2, "{}", name);
+        assert_eq!(outputs(&parallel), files, "{}", name);
+        fs::remove_dir_all(&sequential).unwrap();
+        fs::remove_dir_all(&parallel).unwrap();
+    }
+}
@@ -15,7 +15,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         loop {
             reader.peek();
             reader.get();
-            if reader.peek() == None {
+            if reader.peek().is_none() {
                 break;
             }
         }
@@ -1,5 +1,6 @@
 use std::io::{Read, Seek, SeekFrom};
 use std::fs::File;
+use memchr::{memchr, memchr2, memchr3};
 
 // const DEFAULT_BUF_SIZE: usize = 5;
 const DEFAULT_BUF_SIZE: usize = 8 * 1024;
@@ -9,6 +10,8 @@ pub struct Reader {
     cursor: usize,
     file: File,
     bytes_read: usize,
+    // file offset of buffer[0]
+    offset: usize,
 }
 
 impl Reader {
@@ -19,6 +22,7 @@ impl Reader {
             file,
             cursor: 0,
             bytes_read: 0,
+            offset: 0,
         };
 
         reader.fill_buf();
@@ -40,6 +44,19 @@ impl Reader {
         }
     }
 
+    // number of bytes consumed from the start of the file
+    pub fn position(&self) -> usize {
+        self.offset + self.cursor.min(self.bytes_read)
+    }
+
+    // moves the reader to an absolute file offset
+    pub fn seek(&mut self, offset: usize) {
+        self.file.seek(SeekFrom::Start(offset as u64)).expect("unable to seek");
+        self.offset = offset;
+        self.bytes_read = 0;
+        self.fill_buf();
+    }
+
     #[inline(always)]
     pub fn increment_index(&mut self) {
         self.cursor += 1;
@@ -62,11 +79,76 @@ impl Reader {
         self.peek()
     }
 
+    // copies bytes into `collection` until one of the `needles` shows up.
+    // the matched byte stays unread and is returned. up to 3 needles are
+    // scanned with memchr so whole buffers are skipped at SIMD speed.
+    pub fn read_until_any(&mut self, needles: &[u8], collection: &mut Vec<u8>) -> Option<u8> {
+        self.scan(needles, Some(collection))
+    }
+
+    // same as read_until_any without keeping the skipped bytes
+    pub fn skip_until_any(&mut self, needles: &[u8]) -> Option<u8> {
+        self.scan(needles, None)
+    }
+
+    fn scan(&mut self, needles: &[u8], mut collection: Option<&mut Vec<u8>>) -> Option<u8> {
+        loop {
+            if self.cursor >= self.bytes_read {
+                if self.bytes_read == 0 {
+                    return None
+                }
+
+                self.fill_buf();
+                continue;
+            }
+
+            let pending = &self.buffer[self.cursor..self.bytes_read];
+            let found = match needles {
+                [a] => memchr(*a, pending),
+                [a, b] => memchr2(*a, *b, pending),
+                [a, b, c] => memchr3(*a, *b, *c, pending),
+                _ => pending.iter().position(|byte| needles.contains(byte)),
+            };
+
+            match found {
+                Some(index) => {
+                    if let Some(collection) = collection.as_mut() {
+                        collection.extend_from_slice(&pending[..index]);
+                    }
+                    self.cursor += index;
+                    return Some(pending[index])
+                },
+                None => {
+                    if let Some(collection) = collection.as_mut() {
+                        collection.extend_from_slice(pending);
+                    }
+                    self.cursor = self.bytes_read;
+                }
+            }
+        }
+    }
+
+    // up to `len` bytes from the current position, nothing is consumed.
+    // fewer bytes come back near the end of the file.
+    pub fn peek_ahead(&mut self, len: usize) -> Vec<u8> {
+        let end = self.bytes_read.min(self.cursor + len);
+        let mut ahead = self.buffer[self.cursor.min(end)..end].to_vec();
+        if ahead.len() < len && self.bytes_read > 0 {
+            // the rest is beyond the buffer, read it and move back
+            let mut tmp_buff = vec![0; len - ahead.len()];
+            let read = self.file.read(&mut tmp_buff).expect("unable to read buff");
+            let _ = self.file.seek(SeekFrom::Current(-(read as i64)));
+            ahead.extend(&tmp_buff[..read]);
+        }
+        ahead
+    }
+
     fn next_in_current_buff(&self) -> bool {
         (self.cursor + 1) < self.bytes_read 
     }
 
     fn fill_buf(&mut self) {
+        self.offset += self.bytes_read;
         let size = self.file.read(&mut self.buffer).expect("unable to read buffer");
         self.bytes_read = size;
         self.cursor = 0;
@@ -130,8 +212,8 @@ mod reader_test{
         assert_eq!(reader.get(), Some(b'8'));
         assert_eq!(reader.get(), Some(b'9'));
         assert_eq!(reader.get(), Some(b'0'));
-        assert_eq!(reader.get().is_none(), true);
-        assert_eq!(reader.get().is_none(), true);
+        assert_eq!(reader.get(), None);
+        assert_eq!(reader.get(), None);
     }
 
     #[test]
@@ -144,6 +226,89 @@ mod reader_test{
         assert_eq!(reader.peek(), Some(b'2'));
     }
 
+    #[test]
+    fn read_until_any(){
+        let file = File::open("../resources/test_db/content.sql").unwrap();
+        let mut reader = Reader::new(file);
+        let mut collection = vec![];
+        assert_eq!(reader.read_until_any(b"59", &mut collection), Some(b'5'));
+        assert_eq!(collection, b"1234");
+        assert_eq!(reader.get(), Some(b'5'));
+
+        let mut collection = vec![];
+        assert_eq!(reader.read_until_any(b"x", &mut collection), None);
+        assert_eq!(collection, b"67890");
+        assert_eq!(reader.get(), None);
+    }
+
+    #[test]
+    fn read_until_more_needles(){
+        // more than memchr takes at once
+        let file = File::open("../resources/test_db/content.sql").unwrap();
+        let mut reader = Reader::new(file);
+        let mut collection = vec![];
+        assert_eq!(reader.read_until_any(b"9876", &mut collection), Some(b'6'));
+        assert_eq!(collection, b"12345");
+        assert_eq!(reader.skip_until_any(b""), None);
+    }
+
+    #[test]
+    fn read_until_any_across_buffers(){
+        // big.sql is larger than a single buffer
+        let file = File::open("../resources/test_db/big.sql").unwrap();
+        let mut reader = Reader::new(file);
+        let mut scanned = vec![];
+        while let Some(byte) = reader.read_until_any(b"();", &mut scanned) {
+            scanned.push(byte);
+            reader.increment_index();
+        }
+
+        let expected = std::fs::read("../resources/test_db/big.sql").unwrap();
+        assert_eq!(scanned, expected);
+    }
+
+    #[test]
+    fn position_and_seek(){
+        let file = File::open("../resources/test_db/big.sql").unwrap();
+        let expected = std::fs::read("../resources/test_db/big.sql").unwrap();
+        let mut reader = Reader::new(file);
+        assert_eq!(reader.position(), 0);
+
+        let mut collection = vec![];
+        reader.read_until_any(b";", &mut collection);
+        assert_eq!(reader.position(), collection.len());
+
+        // land in the middle of the second buffer
+        reader.seek(10_000);
+        assert_eq!(reader.position(), 10_000);
+        assert_eq!(reader.get(), Some(expected[10_000]));
+        assert_eq!(reader.position(), 10_001);
+
+        reader.seek(expected.len());
+        assert_eq!(reader.get(), None);
+        assert_eq!(reader.position(), expected.len());
+    }
+
+    #[test]
+    fn peek_ahead(){
+        let file = File::open("../resources/test_db/big.sql").unwrap();
+        let expected = std::fs::read("../resources/test_db/big.sql").unwrap();
+        let mut reader = Reader::new(file);
+        assert_eq!(reader.peek_ahead(4), &expected[..4]);
+        assert_eq!(reader.get(), Some(expected[0]));
+
+        // across the end of the buffer
+        reader.seek(8 * 1024 - 2);
+        assert_eq!(reader.peek_ahead(5), &expected[8 * 1024 - 2..8 * 1024 + 3]);
+        assert_eq!(reader.get(), Some(expected[8 * 1024 - 2]));
+        assert_eq!(reader.get(), Some(expected[8 * 1024 - 1]));
+        assert_eq!(reader.get(), Some(expected[8 * 1024]));
+
+        reader.seek(expected.len() - 2);
+        assert_eq!(reader.peek_ahead(5), &expected[expected.len() - 2..]);
+        assert_eq!(reader.get(), Some(expected[expected.len() - 2]));
+    }
+
     #[test]
     fn peek_next(){
         let file = File::open("../resources/test_db/content.sql").unwrap();